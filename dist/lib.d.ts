@@ -1,5 +1,143 @@
+export interface Account {
+    account_no: string;
+}
+export type Align = "left" | "center" | "right" | "decimal";
+export declare const Align: Readonly<{ Left: "left"; Center: "center"; Right: "right"; Decimal: "decimal"; }>;
+export type Asset = string | number[] | Buffer;
+export interface BatchError {
+    code: string;
+    message: string;
+    row: number | null;
+    field: string | null;
+    issues: ValidationIssue[];
+}
+export interface BatchItem {
+    member_no: string;
+    path: string | null;
+    manifest: string | null;
+    pages: number | null;
+    error: BatchError | null;
+}
+export interface BfTotals {
+    purchase_units: string;
+    purchase_costs: string;
+    sale_units: string;
+    sale_costs: string;
+    balance_units: string;
+    latest_nav: string;
+}
+export interface Branding {
+    logo: Asset | null;
+    logo_scale: number | null;
+    address: string[] | null;
+    contact: string[] | null;
+    primary_color: string | null;
+    secondary_color: string | null;
+    accent_color: string | null;
+    fonts: FontConfig | null;
+}
+export interface ColumnConfig {
+    field: Field;
+    title: string | null;
+    width: number | null;
+    format: NumberFormat | null;
+    align: Align | null;
+    group: string | null;
+    size: number | null;
+    overflow: Overflow | null;
+}
+export interface ColumnTotal {
+    field: Field;
+    total: string | null;
+}
+export interface Customer {
+    member_no: string;
+    name: string;
+    email: string;
+    post_address: string;
+    phone: string;
+    town: string;
+}
+export type Field = "trans_id" | "trans_date" | "trans_type" | "mop" | "amount" | "taxamt" | "netamount" | "running_balance" | "running_shares" | "shares" | "price" | "p_amount" | "w_amount" | "i_amount" | "deposit" | "interest" | "withdrawal" | "purchase_units" | "purchase_price" | "purchase_cost" | "sale_units" | "sale_price" | "sale_cost";
+export declare const Field: Readonly<{ TransId: "trans_id"; TransDate: "trans_date"; TransType: "trans_type"; Mop: "mop"; Amount: "amount"; Taxamt: "taxamt"; Netamount: "netamount"; RunningBalance: "running_balance"; RunningShares: "running_shares"; Shares: "shares"; Price: "price"; PAmount: "p_amount"; WAmount: "w_amount"; IAmount: "i_amount"; Deposit: "deposit"; Interest: "interest"; Withdrawal: "withdrawal"; PurchaseUnits: "purchase_units"; PurchasePrice: "purchase_price"; PurchaseCost: "purchase_cost"; SaleUnits: "sale_units"; SalePrice: "sale_price"; SaleCost: "sale_cost"; }>;
+export interface FontConfig {
+    regular: Asset;
+    bold: Asset | null;
+}
+export interface Fund {
+    name: string;
+    security_code: string;
+    currency: string;
+    disclaimer: string;
+}
+export interface MmfTotals {
+    running_balance: string;
+    tax: string;
+    deposits: string;
+    withdrawals: string;
+    interest: string;
+}
+export type NumberFormat = "text" | "amount" | "tax" | "balance" | "units" | "price";
+export declare const NumberFormat: Readonly<{ Text: "text"; Amount: "amount"; Tax: "tax"; Balance: "balance"; Units: "units"; Price: "price"; }>;
+export interface OutputConfig {
+    template: string;
+    create_dirs: boolean;
+    overwrite: OverwritePolicy;
+    manifest: boolean;
+}
+export type Overflow = "wrap" | "truncate" | "shrink";
+export declare const Overflow: Readonly<{ Wrap: "wrap"; Truncate: "truncate"; Shrink: "shrink"; }>;
+export type OverwritePolicy = "fail" | "overwrite" | "versioned";
+export declare const OverwritePolicy: Readonly<{ Fail: "fail"; Overwrite: "overwrite"; Versioned: "versioned"; }>;
 export interface Payload {
+    version: number;
     pdf_name: string;
+    customer: Customer;
+    account: Account;
+    fund: Fund;
     transactions: Transaction[];
+    output: OutputConfig | null;
+    rounding: Rounding | null;
+    columns: ColumnConfig[] | null;
+    fonts: FontConfig | null;
+    branding: Branding | null;
+}
+export interface Rounding {
+    amount: RoundingMode;
+    tax: RoundingMode;
+    balance: RoundingMode;
+    units: RoundingMode;
+    price: RoundingMode;
+}
+export type RoundingMode = "half_up" | "bankers";
+export declare const RoundingMode: Readonly<{ HalfUp: "half_up"; Bankers: "bankers"; }>;
+export type Severity = "error" | "warning";
+export declare const Severity: Readonly<{ Error: "error"; Warning: "warning"; }>;
+export interface StatementSummary {
+    member_no: string;
+    account_no: string;
+    security_code: string;
+    pages: number;
+    rows_per_page: number[];
+    period_start: string;
+    closing_date: string;
+    market_value: string;
+    summation: ColumnTotal[];
+    mmf_totals: MmfTotals | null;
+    bf_totals: BfTotals | null;
+    warnings: ValidationIssue[];
+}
+export interface ValidationIssue {
+    code: string;
+    severity: Severity;
+    row: number | null;
+    field: string | null;
+    message: string;
 }
-export declare function generateStatement(payload: string, mmf: boolean): Promise<void>;
+export declare function generateStatement(payload: string, mmf: boolean): Promise<string>;
+export declare function generateStatements(payload: string, mmf: boolean): Promise<BatchItem[]>;
+export declare function renderStatement(payload: string, mmf: boolean): Promise<Buffer>;
+export declare function setOutputRoot(root: string): void;
+export declare function setRenderConcurrency(limit: number): number;
+export declare function summarizeStatement(payload: string, mmf: boolean): Promise<StatementSummary>;
+export declare function validateStatement(payload: string, mmf: boolean): Promise<ValidationIssue[]>;
@@ -12,6 +12,75 @@ function native() {
     return require(modulePath);
 }
 const nativeModuleRef = native();
+exports.Align = Object.freeze({
+    Left: "left",
+    Center: "center",
+    Right: "right",
+    Decimal: "decimal",
+});
+exports.Field = Object.freeze({
+    TransId: "trans_id",
+    TransDate: "trans_date",
+    TransType: "trans_type",
+    Mop: "mop",
+    Amount: "amount",
+    Taxamt: "taxamt",
+    Netamount: "netamount",
+    RunningBalance: "running_balance",
+    RunningShares: "running_shares",
+    Shares: "shares",
+    Price: "price",
+    PAmount: "p_amount",
+    WAmount: "w_amount",
+    IAmount: "i_amount",
+    Deposit: "deposit",
+    Interest: "interest",
+    Withdrawal: "withdrawal",
+    PurchaseUnits: "purchase_units",
+    PurchasePrice: "purchase_price",
+    PurchaseCost: "purchase_cost",
+    SaleUnits: "sale_units",
+    SalePrice: "sale_price",
+    SaleCost: "sale_cost",
+});
+exports.NumberFormat = Object.freeze({
+    Text: "text",
+    Amount: "amount",
+    Tax: "tax",
+    Balance: "balance",
+    Units: "units",
+    Price: "price",
+});
+exports.Overflow = Object.freeze({
+    Wrap: "wrap",
+    Truncate: "truncate",
+    Shrink: "shrink",
+});
+exports.OverwritePolicy = Object.freeze({
+    Fail: "fail",
+    Overwrite: "overwrite",
+    Versioned: "versioned",
+});
+exports.RoundingMode = Object.freeze({
+    HalfUp: "half_up",
+    Bankers: "bankers",
+});
+exports.Severity = Object.freeze({
+    Error: "error",
+    Warning: "warning",
+});
 
 const { generateStatement } = nativeModuleRef;
-exports.generateStatement = generateStatement;
\ No newline at end of file
+exports.generateStatement = generateStatement;
+const { generateStatements } = nativeModuleRef;
+exports.generateStatements = generateStatements;
+const { renderStatement } = nativeModuleRef;
+exports.renderStatement = renderStatement;
+const { setOutputRoot } = nativeModuleRef;
+exports.setOutputRoot = setOutputRoot;
+const { setRenderConcurrency } = nativeModuleRef;
+exports.setRenderConcurrency = setRenderConcurrency;
+const { summarizeStatement } = nativeModuleRef;
+exports.summarizeStatement = summarizeStatement;
+const { validateStatement } = nativeModuleRef;
+exports.validateStatement = validateStatement;
@@ -0,0 +1,133 @@
+#ifndef FULL_STATEMENT_H
+#define FULL_STATEMENT_H
+
+/* Generated with cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define STATEMENT_OK 0
+
+#define STATEMENT_INVALID_PAYLOAD 1
+
+#define STATEMENT_MISSING_FIELD 2
+
+#define STATEMENT_IO_ERROR 3
+
+#define STATEMENT_LOGO_ERROR 4
+
+#define STATEMENT_RENDER_ERROR 5
+
+#define STATEMENT_PANIC 6
+
+#define STATEMENT_INVALID_CONFIG 7
+
+#define STATEMENT_UNSAFE_PATH 8
+
+#define STATEMENT_INVALID_DATA 9
+
+#define STATEMENT_FONT_ERROR 10
+
+/**
+ * Current payload schema. Payloads without a `version` are read as the
+ * legacy flat format.
+ */
+#define PAYLOAD_VERSION 2
+
+/**
+ * Renders the statement and writes it below the output root, where the
+ * payload's `output` configuration points.
+ *
+ * Returns `STATEMENT_OK` and, when `path_out` is not null, the written path.
+ * On failure returns a non-zero status and, when `error_out` is not null,
+ * a message describing it.
+ *
+ * # Safety
+ *
+ * `payload` must be null or a valid NUL-terminated string. `path_out` and
+ * `error_out` must each be null or valid for a pointer write.
+ */
+int statement_generate(const char *payload, bool mmf, char **path_out, char **error_out);
+
+/**
+ * Renders the statement into memory without touching the filesystem.
+ *
+ * On success `*pdf_out` and `*len_out` describe a buffer that must be
+ * released with `statement_free_buffer`.
+ *
+ * # Safety
+ *
+ * `payload` must be null or a valid NUL-terminated string. `pdf_out` and
+ * `len_out` must be valid for a write; `error_out` must be null or valid
+ * for a write.
+ */
+int statement_render(const char *payload,
+                     bool mmf,
+                     uint8_t **pdf_out,
+                     size_t *len_out,
+                     char **error_out);
+
+/**
+ * Dry run: computes the statement's pages, totals and warnings without
+ * rendering it.
+ *
+ * On success `*summary_out` is set to a JSON object with `pages`,
+ * `rows_per_page`, `closing_date`, `market_value`, `summation`,
+ * `mmf_totals` or `bf_totals`, and `warnings`.
+ *
+ * # Safety
+ *
+ * `payload` must be null or a valid NUL-terminated string. `summary_out`
+ * must be valid for a write; `error_out` must be null or valid for a write.
+ */
+int statement_summarize(const char *payload, bool mmf, char **summary_out, char **error_out);
+
+/**
+ * Checks the payload's rows without rendering.
+ *
+ * Returns `STATEMENT_OK` when the payload could be read, whether or not
+ * problems were found, and sets `*report_out` to a JSON array of issues
+ * (`code`, `severity`, `row`, `field`, `message`).
+ *
+ * # Safety
+ *
+ * `payload` must be null or a valid NUL-terminated string. `report_out`
+ * must be valid for a write; `error_out` must be null or valid for a write.
+ */
+int statement_validate(const char *payload, bool mmf, char **report_out, char **error_out);
+
+/**
+ * Sets the directory `statement_generate` writes under, `storage` by
+ * default. Payloads can only choose paths below it.
+ *
+ * # Safety
+ *
+ * `root` must be null or a valid NUL-terminated string. `error_out` must be
+ * null or valid for a write.
+ */
+int statement_set_output_root(const char *root, char **error_out);
+
+/**
+ * Releases a buffer returned by `statement_render`.
+ *
+ * # Safety
+ *
+ * `data` and `len` must come from a single successful `statement_render`
+ * call and must not be freed twice.
+ */
+void statement_free_buffer(uint8_t *data, size_t len);
+
+/**
+ * Releases a path or error message returned by this library.
+ *
+ * # Safety
+ *
+ * `value` must be null or a string returned through `path_out`,
+ * `summary_out`, `report_out` or `error_out`, and must not be freed twice.
+ */
+void statement_free_string(char *value);
+
+#endif  /* FULL_STATEMENT_H */
@@ -2,31 +2,36 @@ const fs = require("./dist/lib.js");
 
 fs.generateStatement(
   JSON.stringify({
+    version: 2,
+    customer: {
+      member_no: "00020",
+      name: "Japheth Kiptoo",
+      email: "jk@gmail.com",
+      post_address: "Utawala, Nairobi",
+      phone: "0724765149",
+      town: "Litein",
+    },
+    account: { account_no: "001-00020-001" },
+    fund: {
+      name: "MMF",
+      security_code: "001",
+      currency: "KES",
+      disclaimer: "lorem blaaa blaaaa",
+    },
     transactions: [
       {
         amount: 100,
-        member_no: "00020",
-        town: "Litein",
-        e_mail: "jk@gmail.com",
-        allnames: "Japheth Kiptoo",
-        post_address: "Utawala, Nairobi",
-        gsm_no: "0724765149",
-        descript: "MMF",
-        security_code: "001",
         trans_id: 2324,
         trans_date: new Date(),
-        account_no: "001-00020-001",
         taxamt: 0,
         trans_type: "WITHDRAWAL",
         running_balance: 0.00058986,
         running_shares: 0,
         netamount: 0,
         mop: "M-PESA",
-        currency: "KES",
         p_amount: 0,
         w_amount: 0,
         i_amount: 0,
-        statement: "lorem blaaa blaaaa",
         price: 0,
         shares: 0,
       },
@@ -35,8 +40,8 @@ fs.generateStatement(
   }),
   true
 )
-  .then(() => {
-    console.log("success");
+  .then((path) => {
+    console.log("success", path);
   })
   .catch((e) => {
     console.log(e);
@@ -0,0 +1,168 @@
+// Regenerates dist/lib.js and dist/lib.d.ts with tslink, then fixes up what
+// tslink 0.1 cannot express:
+//
+// - enums are exported under their serde wire names (`rename_all` on the
+//   Rust enum), as a string union type plus a frozen object of constants
+// - float fields are typed `number`
+// - `Asset` (an untagged serde enum) is declared as a path, bytes or Buffer
+// - `renderStatement` resolves with a Node `Buffer`
+//
+// tslink writes declarations in no fixed order, so they are sorted by name to
+// keep the output stable. CI runs this and fails if dist/ changes.
+//
+// Usage: node scripts/dist.js
+
+"use strict";
+
+const { execFileSync } = require("child_process");
+const fs = require("fs");
+const path = require("path");
+
+const root = path.resolve(__dirname, "..");
+const dist = path.join(root, "dist");
+
+const ASSET = "export type Asset = string | number[] | Buffer;\n";
+const RETURN_TYPES = { PdfBuffer: "Buffer" };
+
+function rustFiles(dir) {
+  return fs.readdirSync(dir, { withFileTypes: true }).flatMap((entry) => {
+    const file = path.join(dir, entry.name);
+    if (entry.isDirectory()) return rustFiles(file);
+    return entry.name.endsWith(".rs") ? [file] : [];
+  });
+}
+
+// The serde `rename_all` rule of every enum exported with #[tslink].
+function enumRenames() {
+  const renames = {};
+  for (const file of rustFiles(path.join(root, "src"))) {
+    const source = fs.readFileSync(file, "utf8");
+    const pattern = /((?:#\[[^\]]*\]\s*)+)pub enum (\w+)/g;
+    for (const [, attrs, name] of source.matchAll(pattern)) {
+      if (!/#\[tslink/.test(attrs)) continue;
+      const rule = attrs.match(/rename_all\s*=\s*"(\w+)"/);
+      renames[name] = rule ? rule[1] : null;
+    }
+  }
+  return renames;
+}
+
+function wireName(variant, rule) {
+  const words = variant.replace(/(?<!^)([A-Z])/g, "_$1");
+  switch (rule) {
+    case null:
+      return variant;
+    case "snake_case":
+      return words.toLowerCase();
+    case "SCREAMING_SNAKE_CASE":
+      return words.toUpperCase();
+    case "lowercase":
+      return variant.toLowerCase();
+    default:
+      throw new Error(`unsupported rename_all rule: ${rule}`);
+  }
+}
+
+// Splits `text` into blocks that each start at a line matching `start`.
+function blocks(text, start) {
+  const out = [];
+  for (const line of text.split("\n")) {
+    if (start.test(line) || out.length === 0) out.push([]);
+    out[out.length - 1].push(line);
+  }
+  return out.map((lines) => lines.join("\n").trimEnd() + "\n");
+}
+
+function declName(block) {
+  return block.match(/^export (?:declare )?(?:function|interface|enum|type|const) (\w+)/)[1];
+}
+
+function byName(a, b) {
+  return a.name < b.name ? -1 : a.name > b.name ? 1 : 0;
+}
+
+function fixDeclarations(text, renames) {
+  const decls = blocks(text, /^export /).map((block) => {
+    const name = declName(block);
+    const variants = block.match(/^export enum \w+ \{\n([^}]*)\}/);
+    if (!variants) {
+      let fixed = block.replace(/\b(f32|f64)\b/g, "number");
+      for (const [from, to] of Object.entries(RETURN_TYPES)) {
+        fixed = fixed.replace(new RegExp(`Promise<${from}>`, "g"), `Promise<${to}>`);
+      }
+      return { name, block: fixed };
+    }
+
+    const names = variants[1].match(/\w+/g);
+    const wire = names.map((variant) => wireName(variant, renames[name]));
+    const union = wire.map((value) => `"${value}"`).join(" | ");
+    const consts = names.map((variant, i) => `${variant}: "${wire[i]}";`).join(" ");
+    return {
+      name,
+      block:
+        `export type ${name} = ${union};\n` +
+        `export declare const ${name}: Readonly<{ ${consts} }>;\n`,
+    };
+  });
+  if (/\bAsset\b/.test(text) && !decls.some((decl) => decl.name === "Asset")) {
+    decls.push({ name: "Asset", block: ASSET });
+  }
+
+  return decls.sort(byName).map((decl) => decl.block).join("");
+}
+
+function fixModule(text, renames) {
+  const marker = "const nativeModuleRef = native();\n";
+  const split = text.indexOf(marker) + marker.length;
+  const header = text.slice(0, split);
+  const body = text.slice(split);
+
+  const enums = [...body.matchAll(/exports\.(\w+) = Object\.freeze\(\{\n([^}]*)\}\);/g)]
+    .map(([, name, lines]) => {
+      const names = [...lines.matchAll(/^\s*(\w+): /gm)].map(([, variant]) => variant);
+      const constants = names
+        .map((variant) => `    ${variant}: "${wireName(variant, renames[name])}",\n`)
+        .join("");
+      return { name, block: `exports.${name} = Object.freeze({\n${constants}});\n` };
+    })
+    .sort(byName);
+
+  const functions = [...body.matchAll(/const \{ (\w+) \} = nativeModuleRef;\nexports\.\w+ = \w+;/g)]
+    .map(([block, name]) => ({ name, block }))
+    .sort(byName);
+
+  return (
+    header +
+    enums.map((e) => e.block).join("") +
+    "\n" +
+    functions.map((f) => f.block).join("\n") +
+    "\n"
+  );
+}
+
+function main() {
+  const packageJson = path.join(dist, "package.json");
+  // tslink stamps the crate version; the npm package is versioned on its own
+  const version = JSON.parse(fs.readFileSync(packageJson, "utf8")).version;
+
+  // tslink writes dist/ while the proc macros expand, so make cargo rebuild
+  const now = new Date();
+  fs.utimesSync(path.join(root, "src", "lib.rs"), now, now);
+  execFileSync("cargo", ["build", "--lib", "--quiet"], {
+    cwd: root,
+    env: { ...process.env, TSLINK_BUILD: "true" },
+    stdio: "inherit",
+  });
+
+  const pkg = JSON.parse(fs.readFileSync(packageJson, "utf8"));
+  pkg.version = version;
+  fs.writeFileSync(packageJson, JSON.stringify(pkg, null, 4));
+
+  const renames = enumRenames();
+  const dts = path.join(dist, "lib.d.ts");
+  fs.writeFileSync(dts, fixDeclarations(fs.readFileSync(dts, "utf8"), renames));
+  const js = path.join(dist, "lib.js");
+  fs.writeFileSync(js, fixModule(fs.readFileSync(js, "utf8"), renames));
+}
+
+main();
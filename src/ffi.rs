@@ -0,0 +1,271 @@
+//! C entry points for services that link the shared library directly.
+//!
+//! Inputs are borrowed and never freed here. Every buffer or string handed
+//! back is owned by the caller and must be released with the matching
+//! `statement_free_*` function. Panics are caught before they cross the
+//! boundary and reported as `STATEMENT_PANIC`.
+
+use std::{
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::pdf::{
+    error::StatementError,
+    output,
+    payload::Payload,
+    util::{create_pdf, render_pdf, summarize_pdf},
+    validate::validate,
+};
+
+pub const STATEMENT_OK: c_int = 0;
+pub const STATEMENT_INVALID_PAYLOAD: c_int = 1;
+pub const STATEMENT_MISSING_FIELD: c_int = 2;
+pub const STATEMENT_IO_ERROR: c_int = 3;
+pub const STATEMENT_LOGO_ERROR: c_int = 4;
+pub const STATEMENT_RENDER_ERROR: c_int = 5;
+pub const STATEMENT_PANIC: c_int = 6;
+pub const STATEMENT_INVALID_CONFIG: c_int = 7;
+pub const STATEMENT_UNSAFE_PATH: c_int = 8;
+pub const STATEMENT_INVALID_DATA: c_int = 9;
+pub const STATEMENT_FONT_ERROR: c_int = 10;
+
+/// Renders the statement and writes it below the output root, where the
+/// payload's `output` configuration points.
+///
+/// Returns `STATEMENT_OK` and, when `path_out` is not null, the written path.
+/// On failure returns a non-zero status and, when `error_out` is not null,
+/// a message describing it.
+///
+/// # Safety
+///
+/// `payload` must be null or a valid NUL-terminated string. `path_out` and
+/// `error_out` must each be null or valid for a pointer write.
+#[no_mangle]
+pub unsafe extern "C" fn statement_generate(
+    payload: *const c_char,
+    mmf: bool,
+    path_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    guard(error_out, || {
+        let data = read_payload(payload)?;
+        let output = create_pdf(&data, mmf)?;
+
+        if !path_out.is_null() {
+            *path_out = into_c_string(output.path.display().to_string());
+        }
+
+        Ok(())
+    })
+}
+
+/// Renders the statement into memory without touching the filesystem.
+///
+/// On success `*pdf_out` and `*len_out` describe a buffer that must be
+/// released with `statement_free_buffer`.
+///
+/// # Safety
+///
+/// `payload` must be null or a valid NUL-terminated string. `pdf_out` and
+/// `len_out` must be valid for a write; `error_out` must be null or valid
+/// for a write.
+#[no_mangle]
+pub unsafe extern "C" fn statement_render(
+    payload: *const c_char,
+    mmf: bool,
+    pdf_out: *mut *mut u8,
+    len_out: *mut usize,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    guard(error_out, || {
+        if pdf_out.is_null() || len_out.is_null() {
+            return Err(StatementError::InvalidPayload(
+                "output buffer pointers must not be null".to_string(),
+            ));
+        }
+
+        let data = read_payload(payload)?;
+        let bytes = render_pdf(&data, mmf)?.bytes.into_boxed_slice();
+
+        *len_out = bytes.len();
+        *pdf_out = Box::into_raw(bytes) as *mut u8;
+
+        Ok(())
+    })
+}
+
+/// Dry run: computes the statement's pages, totals and warnings without
+/// rendering it.
+///
+/// On success `*summary_out` is set to a JSON object with `pages`,
+/// `rows_per_page`, `closing_date`, `market_value`, `summation`,
+/// `mmf_totals` or `bf_totals`, and `warnings`.
+///
+/// # Safety
+///
+/// `payload` must be null or a valid NUL-terminated string. `summary_out`
+/// must be valid for a write; `error_out` must be null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn statement_summarize(
+    payload: *const c_char,
+    mmf: bool,
+    summary_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    guard(error_out, || {
+        if summary_out.is_null() {
+            return Err(StatementError::InvalidPayload(
+                "summary pointer must not be null".to_string(),
+            ));
+        }
+
+        let data = read_payload(payload)?;
+        let summary = serde_json::to_string(&summarize_pdf(&data, mmf)?)?;
+        *summary_out = into_c_string(summary);
+
+        Ok(())
+    })
+}
+
+/// Checks the payload's rows without rendering.
+///
+/// Returns `STATEMENT_OK` when the payload could be read, whether or not
+/// problems were found, and sets `*report_out` to a JSON array of issues
+/// (`code`, `severity`, `row`, `field`, `message`).
+///
+/// # Safety
+///
+/// `payload` must be null or a valid NUL-terminated string. `report_out`
+/// must be valid for a write; `error_out` must be null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn statement_validate(
+    payload: *const c_char,
+    mmf: bool,
+    report_out: *mut *mut c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    guard(error_out, || {
+        if report_out.is_null() {
+            return Err(StatementError::InvalidPayload(
+                "report pointer must not be null".to_string(),
+            ));
+        }
+
+        let data = read_payload(payload)?;
+        let report = serde_json::to_string(&validate(&data, mmf))?;
+        *report_out = into_c_string(report);
+
+        Ok(())
+    })
+}
+
+/// Sets the directory `statement_generate` writes under, `storage` by
+/// default. Payloads can only choose paths below it.
+///
+/// # Safety
+///
+/// `root` must be null or a valid NUL-terminated string. `error_out` must be
+/// null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn statement_set_output_root(
+    root: *const c_char,
+    error_out: *mut *mut c_char,
+) -> c_int {
+    guard(error_out, || {
+        if root.is_null() {
+            return Err(StatementError::InvalidConfig(
+                "output root is null".to_string(),
+            ));
+        }
+
+        let root = CStr::from_ptr(root)
+            .to_str()
+            .map_err(|err| StatementError::InvalidConfig(err.to_string()))?;
+        output::set_root(root.into());
+
+        Ok(())
+    })
+}
+
+/// Releases a buffer returned by `statement_render`.
+///
+/// # Safety
+///
+/// `data` and `len` must come from a single successful `statement_render`
+/// call and must not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn statement_free_buffer(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+/// Releases a path or error message returned by this library.
+///
+/// # Safety
+///
+/// `value` must be null or a string returned through `path_out`,
+/// `summary_out`, `report_out` or `error_out`, and must not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn statement_free_string(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+unsafe fn guard<F>(error_out: *mut *mut c_char, job: F) -> c_int
+where
+    F: FnOnce() -> Result<(), StatementError>,
+{
+    if !error_out.is_null() {
+        *error_out = ptr::null_mut();
+    }
+
+    let (status, message) = match catch_unwind(AssertUnwindSafe(job)) {
+        Ok(Ok(())) => return STATEMENT_OK,
+        Ok(Err(err)) => (status_code(&err), err.to_string()),
+        Err(_) => (STATEMENT_PANIC, "statement rendering panicked".to_string()),
+    };
+
+    if !error_out.is_null() {
+        *error_out = into_c_string(message);
+    }
+
+    status
+}
+
+unsafe fn read_payload(payload: *const c_char) -> Result<Payload, StatementError> {
+    if payload.is_null() {
+        return Err(StatementError::InvalidPayload(
+            "payload is null".to_string(),
+        ));
+    }
+
+    let json = CStr::from_ptr(payload)
+        .to_str()
+        .map_err(|err| StatementError::InvalidPayload(err.to_string()))?;
+
+    Ok(Payload::from_json(json)?)
+}
+
+fn status_code(err: &StatementError) -> c_int {
+    match err {
+        StatementError::InvalidPayload(_) => STATEMENT_INVALID_PAYLOAD,
+        StatementError::Validation(_) if err.code() == "MISSING_FIELD" => STATEMENT_MISSING_FIELD,
+        StatementError::Validation(_) => STATEMENT_INVALID_DATA,
+        StatementError::Io { .. } => STATEMENT_IO_ERROR,
+        StatementError::Logo { .. } => STATEMENT_LOGO_ERROR,
+        StatementError::Render(_) => STATEMENT_RENDER_ERROR,
+        StatementError::Font(_) => STATEMENT_FONT_ERROR,
+        StatementError::InvalidConfig(_) => STATEMENT_INVALID_CONFIG,
+        StatementError::UnsafePath(_) => STATEMENT_UNSAFE_PATH,
+    }
+}
+
+fn into_c_string(value: String) -> *mut c_char {
+    CString::new(value.replace('\0', ""))
+        .unwrap_or_default()
+        .into_raw()
+}
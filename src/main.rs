@@ -1,27 +1,277 @@
+#[allow(dead_code)]
 mod pdf;
 
-use crate::pdf::util::*;
-use serde::Deserialize;
-use std::{ffi::CString, os::raw::c_char};
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use futures::executor::block_on;
+
+use crate::pdf::{
+    batch::{generate_batch, parse_batch, parse_entry, BatchEntry, BatchItem},
+    branding::Branding,
+    error::StatementError,
+    output::{self, write_replacing},
+    payload::Payload,
+    util::{create_pdf, render_pdf, summarize_pdf},
+    validate::validate,
+};
+
+/// Generate member statements from JSON payloads without going through Node.
+#[derive(Parser)]
+#[command(name = "full_statement", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Directory statements are written under; payloads only choose paths
+    /// below it.
+    #[arg(long, global = true, default_value = "storage")]
+    output_root: PathBuf,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render a single payload.
+    Render {
+        /// Payload JSON file.
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long, value_enum)]
+        fund_type: FundType,
+        /// Write the PDF here instead of the payload's output location.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Print the pages, totals and warnings as JSON instead of writing
+        /// a PDF.
+        #[arg(long, conflicts_with = "out")]
+        dry_run: bool,
+        /// Branding JSON for payloads that don't carry their own.
+        #[arg(long)]
+        branding: Option<PathBuf>,
+    },
+    /// Check a payload's rows without rendering and print one JSON line per
+    /// issue. Exits non-zero if any issue is an error.
+    Validate {
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long, value_enum)]
+        fund_type: FundType,
+    },
+    /// Render every payload in a directory of JSON files or a JSON-lines file
+    /// and print one JSON report line per member.
+    Batch {
+        #[arg(long)]
+        input: PathBuf,
+        #[arg(long, value_enum)]
+        fund_type: FundType,
+        /// Branding JSON for payloads that don't carry their own.
+        #[arg(long)]
+        branding: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FundType {
+    Mmf,
+    Bf,
+}
 
-#[derive(Debug, Deserialize)]
-struct Payload {
-    pdf_name: String,
-    transactions: Vec<Transaction>,
+impl FundType {
+    fn is_mmf(self) -> bool {
+        matches!(self, FundType::Mmf)
+    }
 }
 
-#[no_mangle]
-pub extern "C" fn generate_statement(payload: *const c_char, mmf: bool) {
-    let c_str = unsafe {
-        assert!(!payload.is_null());
-        CString::from_raw(payload as *mut c_char)
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    output::set_root(cli.output_root);
+
+    let result = match cli.command {
+        Command::Render {
+            input,
+            fund_type,
+            out,
+            dry_run,
+            branding,
+        } => render(&input, fund_type, out, dry_run, branding.as_deref()),
+        Command::Validate { input, fund_type } => check(&input, fund_type),
+        Command::Batch {
+            input,
+            fund_type,
+            branding,
+        } => batch(&input, fund_type, branding.as_deref()),
     };
 
-    let json_str = c_str.to_str().expect("Data failed to load");
-    let data: Payload = serde_json::from_str(json_str).expect("Failed to load data");
-    println!("{:?}", data.transactions[0]);
-    create_pdf(data.transactions, data.pdf_name, mmf);
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
 
-#[allow(dead_code)]
-fn main() {}
+fn render(
+    input: &Path,
+    fund_type: FundType,
+    out: Option<PathBuf>,
+    dry_run: bool,
+    branding: Option<&Path>,
+) -> Result<ExitCode, StatementError> {
+    let mut data = Payload::from_json(&read_file(input)?)?;
+    if let Some(branding) = branding {
+        apply_branding([&mut data], branding)?;
+    }
+
+    if dry_run {
+        let summary = summarize_pdf(&data, fund_type.is_mmf())?;
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let path = match out {
+        Some(path) => write_replacing(path, &render_pdf(&data, fund_type.is_mmf())?.bytes)?,
+        None => create_pdf(&data, fund_type.is_mmf())?.path,
+    };
+
+    println!("{}", path.display());
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn check(input: &Path, fund_type: FundType) -> Result<ExitCode, StatementError> {
+    let data = Payload::from_json(&read_file(input)?)?;
+    let issues = validate(&data, fund_type.is_mmf());
+
+    for issue in &issues {
+        println!("{}", serde_json::to_string(issue)?);
+    }
+
+    let errors = issues.iter().filter(|issue| issue.is_error()).count();
+    if errors > 0 {
+        eprintln!("{} errors, {} warnings", errors, issues.len() - errors);
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn batch(
+    input: &Path,
+    fund_type: FundType,
+    branding: Option<&Path>,
+) -> Result<ExitCode, StatementError> {
+    let mut entries = if input.is_dir() {
+        read_dir_payloads(input)?
+    } else {
+        read_lines_payloads(input)?
+    };
+    if let Some(branding) = branding {
+        apply_branding(entries.iter_mut().flatten(), branding)?;
+    }
+
+    let report = block_on(generate_batch(entries, fund_type.is_mmf()));
+    let failed = report.iter().filter(|item| item.error.is_some()).count();
+
+    for item in &report {
+        println!("{}", serde_json::to_string(item)?);
+    }
+
+    if failed > 0 {
+        eprintln!("{} of {} statements failed", failed, report.len());
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Gives the branding in `file` to every payload without one of its own.
+fn apply_branding<'a>(
+    payloads: impl IntoIterator<Item = &'a mut Payload>,
+    file: &Path,
+) -> Result<(), StatementError> {
+    let branding: Branding = serde_json::from_str(&read_file(file)?)
+        .map_err(|err| StatementError::InvalidConfig(format!("{}: {}", file.display(), err)))?;
+
+    for payload in payloads {
+        payload.branding.get_or_insert_with(|| branding.clone());
+    }
+
+    Ok(())
+}
+
+/// Each `*.json` file holds a payload, an array of payloads, or a legacy
+/// payload spanning several members. A file that cannot be read is
+/// reported as a failed entry and the rest are still rendered.
+fn read_dir_payloads(dir: &Path) -> Result<Vec<BatchEntry>, StatementError> {
+    let io_error = |source| StatementError::Io {
+        path: dir.to_path_buf(),
+        source,
+    };
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()
+        .map_err(io_error)?;
+    files.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    files.sort();
+
+    let mut entries = vec![];
+    for file in files {
+        let source = file.display().to_string();
+        let batch = read_file(&file).and_then(|json| {
+            parse_batch(&json).map_err(|err| StatementError::InvalidPayload(err.to_string()))
+        });
+        match batch {
+            Ok(batch) => entries.extend(
+                batch
+                    .into_iter()
+                    .map(|entry| entry.map_err(|item| Box::new(item.context(&source)))),
+            ),
+            Err(err) => entries.push(Err(Box::new(
+                BatchItem::failed(String::new(), err).context(&source),
+            ))),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// One payload per line; blank lines are skipped. A line that cannot be
+/// read is reported as a failed entry and the rest are still rendered.
+fn read_lines_payloads(file: &Path) -> Result<Vec<BatchEntry>, StatementError> {
+    let io_error = |source| StatementError::Io {
+        path: file.to_path_buf(),
+        source,
+    };
+    let reader = BufReader::new(fs::File::open(file).map_err(io_error)?);
+
+    let mut entries = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let source = format!("{} line {}", file.display(), i + 1);
+        let entry = match serde_json::from_str(&line) {
+            Ok(value) => parse_entry(value),
+            Err(err) => Err(Box::new(BatchItem::failed(String::new(), err.into()))),
+        };
+        entries.push(entry.map_err(|item| Box::new(item.context(&source))));
+    }
+
+    Ok(entries)
+}
+
+fn read_file(path: &Path) -> Result<String, StatementError> {
+    fs::read_to_string(path).map_err(|source| StatementError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
@@ -0,0 +1,171 @@
+use std::ptr;
+
+use crate::pdf::{
+    batch::{generate_batch, parse_batch, BatchItem},
+    error::StatementError,
+    output, pool,
+};
+use node_bindgen::{
+    core::{
+        val::{JsEnv, JsObject},
+        NjError, TryIntoJs,
+    },
+    derive::node_bindgen,
+    sys::{napi_create_buffer_copy, napi_status_napi_ok, napi_value},
+};
+use tslink::tslink;
+
+use crate::pdf::{
+    payload::Payload,
+    summary::StatementSummary,
+    util::{create_pdf, render_pdf, summarize_pdf},
+    validate::{validate, ValidationIssue},
+};
+
+/// Rejects the promise with an `Error` carrying `code`, `row`, `field` and
+/// the validation `issues` so callers can report per member without parsing
+/// the message.
+impl TryIntoJs for StatementError {
+    fn try_to_js(self, js_env: &JsEnv) -> Result<napi_value, NjError> {
+        let error = js_env.create_error(&self.to_string())?;
+        let mut object = JsObject::new(*js_env, error);
+
+        object.set_property("code", self.code().to_string().try_to_js(js_env)?)?;
+        object.set_property("row", self.row().map(|row| row as u32).try_to_js(js_env)?)?;
+        object.set_property("field", self.field().map(str::to_string).try_to_js(js_env)?)?;
+        object.set_property("issues", self.issues().to_vec().try_to_js(js_env)?)?;
+
+        Ok(error)
+    }
+}
+
+/// Rendered PDF handed to Node as a `Buffer`.
+struct PdfBuffer(Vec<u8>);
+
+impl TryIntoJs for PdfBuffer {
+    fn try_to_js(self, js_env: &JsEnv) -> Result<napi_value, NjError> {
+        let mut buffer = ptr::null_mut();
+
+        let status = unsafe {
+            napi_create_buffer_copy(
+                js_env.inner(),
+                self.0.len(),
+                self.0.as_ptr() as *const _,
+                ptr::null_mut(),
+                &mut buffer,
+            )
+        };
+
+        if status != napi_status_napi_ok {
+            return Err(NjError::NapiCall(status.into()));
+        }
+
+        Ok(buffer)
+    }
+}
+
+impl TryIntoJs for BatchItem {
+    fn try_to_js(self, js_env: &JsEnv) -> Result<napi_value, NjError> {
+        serde_json::to_value(self)
+            .map_err(|err| NjError::Other(err.to_string()))?
+            .try_to_js(js_env)
+    }
+}
+
+impl TryIntoJs for StatementSummary {
+    fn try_to_js(self, js_env: &JsEnv) -> Result<napi_value, NjError> {
+        serde_json::to_value(self)
+            .map_err(|err| NjError::Other(err.to_string()))?
+            .try_to_js(js_env)
+    }
+}
+
+impl TryIntoJs for ValidationIssue {
+    fn try_to_js(self, js_env: &JsEnv) -> Result<napi_value, NjError> {
+        serde_json::to_value(self)
+            .map_err(|err| NjError::Other(err.to_string()))?
+            .try_to_js(js_env)
+    }
+}
+
+#[tslink]
+#[node_bindgen]
+async fn generate_statement(payload: String, mmf: bool) -> Result<String, StatementError> {
+    pool::spawn(move || {
+        let data = Payload::from_json(payload.as_str())?;
+
+        let output = create_pdf(&data, mmf)?;
+
+        Ok(output.path.display().to_string())
+    })
+    .await
+}
+
+#[tslink]
+#[node_bindgen]
+async fn render_statement(payload: String, mmf: bool) -> Result<PdfBuffer, StatementError> {
+    pool::spawn(move || {
+        let data = Payload::from_json(payload.as_str())?;
+
+        Ok(PdfBuffer(render_pdf(&data, mmf)?.bytes))
+    })
+    .await
+}
+
+/// Accepts a JSON array of payloads, or one legacy payload holding many
+/// members' transactions, and resolves with a report entry per member.
+#[tslink]
+#[node_bindgen]
+async fn generate_statements(payload: String, mmf: bool) -> Result<Vec<BatchItem>, StatementError> {
+    let payloads = pool::spawn(move || Ok(parse_batch(payload.as_str())?)).await?;
+
+    Ok(generate_batch(payloads, mmf).await)
+}
+
+/// Dry run: resolves with the page count, totals and warnings the statement
+/// would have, without drawing or writing the PDF.
+#[tslink]
+#[node_bindgen]
+async fn summarize_statement(
+    payload: String,
+    mmf: bool,
+) -> Result<StatementSummary, StatementError> {
+    pool::spawn(move || {
+        let data = Payload::from_json(payload.as_str())?;
+
+        summarize_pdf(&data, mmf)
+    })
+    .await
+}
+
+/// Checks the payload's rows without rendering and resolves with every
+/// issue found. The statement renders as long as none of them is an error.
+#[tslink]
+#[node_bindgen]
+async fn validate_statement(
+    payload: String,
+    mmf: bool,
+) -> Result<Vec<ValidationIssue>, StatementError> {
+    pool::spawn(move || {
+        let data = Payload::from_json(payload.as_str())?;
+
+        Ok(validate(&data, mmf))
+    })
+    .await
+}
+
+/// Sets the directory `generateStatement` and `generateStatements` write
+/// under, `storage` by default. Payloads can only choose paths below it.
+#[tslink]
+#[node_bindgen]
+fn set_output_root(root: String) {
+    output::set_root(root.into());
+}
+
+/// Limits how many statements render in parallel on the worker pool.
+#[tslink]
+#[node_bindgen]
+fn set_render_concurrency(limit: u32) -> u32 {
+    pool::set_concurrency(limit as usize);
+    pool::concurrency() as u32
+}
@@ -0,0 +1,35 @@
+use std::{fs, path::PathBuf};
+
+use serde::Deserialize;
+
+use super::error::StatementError;
+
+/// A file the statement embeds: a path to it, or the file itself as an array
+/// of bytes or a serialized Node `Buffer`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Asset {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+    Buffer { data: Vec<u8> },
+}
+
+impl Asset {
+    pub fn read(&self) -> Result<Vec<u8>, StatementError> {
+        match self {
+            Asset::Path(path) => fs::read(path).map_err(|source| StatementError::Io {
+                path: path.clone(),
+                source,
+            }),
+            Asset::Bytes(data) | Asset::Buffer { data } => Ok(data.clone()),
+        }
+    }
+
+    /// Names the asset in error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            Asset::Path(path) => path.display().to_string(),
+            Asset::Bytes(data) | Asset::Buffer { data } => format!("({} bytes)", data.len()),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tslink::tslink;
+
+use super::{
+    error::StatementError,
+    payload::{LegacyPayload, Payload},
+    pool,
+    util::{create_pdf, StatementOutput},
+    validate::ValidationIssue,
+};
+
+/// One member's payload, or the report entry saying why it could not be
+/// read.
+pub type BatchEntry = Result<Payload, Box<BatchItem>>;
+
+/// Reads a batch: a JSON array of payloads, a single payload, or a legacy
+/// flat payload whose transactions span many members and are split by
+/// `member_no`. Each member is read on its own, so one that doesn't parse
+/// becomes a failed entry instead of rejecting the batch; only malformed
+/// JSON fails the whole call.
+pub fn parse_batch(json: &str) -> Result<Vec<BatchEntry>, serde_json::Error> {
+    Ok(match serde_json::from_str(json)? {
+        Value::Array(items) => items.into_iter().map(parse_entry).collect(),
+        value if Payload::is_legacy(&value) => split_by_member(value),
+        value => vec![parse_entry(value)],
+    })
+}
+
+/// Reads one payload, reporting a failure under the member it names.
+pub fn parse_entry(value: Value) -> BatchEntry {
+    let member_no = if Payload::is_legacy(&value) {
+        value
+            .get("transactions")
+            .and_then(Value::as_array)
+            .and_then(|rows| rows.last())
+            .and_then(|row| row.get("member_no"))
+    } else {
+        value.pointer("/customer/member_no")
+    };
+    let member_no = text(member_no);
+
+    Payload::from_value(value).map_err(|err| Box::new(BatchItem::failed(member_no, err.into())))
+}
+
+/// Splits a legacy payload into one payload per member, keeping members in
+/// the order they first appear and rows in their original order. Rows are
+/// grouped before they are read, so a bad row only fails its own member. A
+/// payload with no transactions is kept as is so the batch reports it.
+fn split_by_member(value: Value) -> Vec<BatchEntry> {
+    let mut fields = match value {
+        Value::Object(fields) => fields,
+        value => return vec![parse_entry(value)],
+    };
+    let rows = match fields.remove("transactions") {
+        Some(Value::Array(rows)) if !rows.is_empty() => rows,
+        rows => {
+            fields.extend(rows.map(|rows| ("transactions".to_string(), rows)));
+            return vec![parse_entry(Value::Object(fields))];
+        }
+    };
+
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<(String, Vec<Value>)> = vec![];
+
+    for row in rows {
+        let member_no = text(row.get("member_no"));
+        match index.get(&member_no) {
+            Some(&i) => groups[i].1.push(row),
+            None => {
+                index.insert(member_no.clone(), groups.len());
+                groups.push((member_no, vec![row]));
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(member_no, rows)| {
+            let mut payload: Map<String, Value> = fields.clone();
+            payload.insert("transactions".to_string(), Value::Array(rows));
+
+            LegacyPayload::deserialize(Value::Object(payload))
+                .map(Payload::from)
+                .map_err(|err| Box::new(BatchItem::failed(member_no, err.into())))
+        })
+        .collect()
+}
+
+/// A member number as the report shows it, whether the JSON holds a string
+/// or a number.
+fn text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[tslink]
+#[derive(Debug, Serialize)]
+pub struct BatchItem {
+    pub member_no: String,
+    pub path: Option<String>,
+    pub manifest: Option<String>,
+    pub pages: Option<usize>,
+    pub error: Option<BatchError>,
+}
+
+#[tslink]
+#[derive(Debug, Serialize)]
+pub struct BatchError {
+    pub code: String,
+    pub message: String,
+    pub row: Option<usize>,
+    pub field: Option<String>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl BatchItem {
+    fn new(member_no: String, result: Result<StatementOutput, StatementError>) -> Self {
+        match result {
+            Ok(output) => BatchItem {
+                member_no,
+                path: Some(output.path.display().to_string()),
+                manifest: output.manifest.map(|path| path.display().to_string()),
+                pages: Some(output.pages),
+                error: None,
+            },
+            Err(err) => BatchItem {
+                member_no,
+                path: None,
+                manifest: None,
+                pages: None,
+                error: Some(BatchError {
+                    code: err.code().to_string(),
+                    message: err.to_string(),
+                    row: err.row(),
+                    field: err.field().map(str::to_string),
+                    issues: err.issues().to_vec(),
+                }),
+            },
+        }
+    }
+
+    pub fn failed(member_no: String, err: StatementError) -> Self {
+        BatchItem::new(member_no, Err(err))
+    }
+
+    /// Prefixes the error message with where the payload was read from.
+    pub fn context(mut self, source: &str) -> Self {
+        if let Some(error) = &mut self.error {
+            error.message = format!("{}: {}", source, error.message);
+        }
+        self
+    }
+}
+
+/// Renders every payload on the worker pool. A failing member is recorded in
+/// its report entry and never stops the rest of the batch; entries that
+/// could not be read are reported as they are.
+pub async fn generate_batch(entries: Vec<BatchEntry>, mmf: bool) -> Vec<BatchItem> {
+    join_all(entries.into_iter().map(|entry| async move {
+        let payload = match entry {
+            Ok(payload) => payload,
+            Err(item) => return *item,
+        };
+        let member_no = payload.customer.member_no.clone();
+        let result = pool::spawn(move || create_pdf(&payload, mmf)).await;
+
+        BatchItem::new(member_no, result)
+    }))
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::parse_batch;
+
+    fn row(member_no: &str, trans_id: i64, trans_type: &str) -> Value {
+        json!({
+            "member_no": member_no,
+            "town": "Nairobi",
+            "e_mail": "member@example.com",
+            "allnames": "A Member",
+            "post_address": "1-00100",
+            "gsm_no": "0700000000",
+            "descript": "MMF",
+            "security_code": "001",
+            "account_no": format!("{}-001", member_no),
+            "currency": "KES",
+            "statement": "",
+            "trans_id": trans_id,
+            "trans_date": "2023-01-01T00:00:00Z",
+            "trans_type": trans_type,
+            "amount": 100,
+            "taxamt": 0,
+            "running_balance": 100,
+            "running_shares": 0,
+            "netamount": 100,
+            "mop": "M-PESA",
+            "p_amount": 100,
+            "w_amount": 0,
+            "i_amount": 0
+        })
+    }
+
+    fn legacy(rows: Vec<Value>) -> Value {
+        json!({ "pdf_name": "statement", "transactions": rows })
+    }
+
+    #[test]
+    fn bad_array_item_fails_only_its_member() {
+        let batch = json!([
+            legacy(vec![row("1", 1, "PURCHASE")]),
+            legacy(vec![row("2", 2, "BONUS")]),
+            legacy(vec![row("3", 3, "PURCHASE")]),
+        ]);
+        let entries = parse_batch(&batch.to_string()).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_ok());
+        assert!(entries[2].is_ok());
+        let failed = entries[1].as_ref().unwrap_err();
+        assert_eq!(failed.member_no, "2");
+        let error = failed.error.as_ref().unwrap();
+        assert_eq!(error.code, "INVALID_PAYLOAD");
+        assert!(error.message.contains("BONUS"), "{}", error.message);
+    }
+
+    #[test]
+    fn bad_legacy_row_fails_only_its_member() {
+        let batch = legacy(vec![
+            row("A", 1, "PURCHASE"),
+            row("B", 2, "BONUS"),
+            row("A", 3, "PURCHASE"),
+            row("C", 4, "PURCHASE"),
+        ]);
+        let entries = parse_batch(&batch.to_string()).unwrap();
+
+        let members: Vec<&str> = entries
+            .iter()
+            .map(|entry| match entry {
+                Ok(payload) => payload.customer.member_no.as_str(),
+                Err(item) => item.member_no.as_str(),
+            })
+            .collect();
+        assert_eq!(members, ["A", "B", "C"]);
+        assert_eq!(entries[0].as_ref().unwrap().transactions.len(), 2);
+        assert!(entries[1].is_err());
+        assert!(entries[2].is_ok());
+    }
+
+    #[test]
+    fn malformed_json_fails_the_batch() {
+        assert!(parse_batch("[{").is_err());
+    }
+}
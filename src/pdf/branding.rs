@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+
+use printpdf::Rgb;
+use serde::Deserialize;
+use tslink::tslink;
+
+use super::{asset::Asset, error::StatementError, fonts::FontConfig};
+
+/// Logo printed when the branding names none, relative to the working
+/// directory.
+const DEFAULT_LOGO: &str = "assets/Logo.jpg";
+
+const DEFAULT_ADDRESS: [&str; 4] = [
+    "P.O Box: 59485-00200",
+    "Nairobi, Kenya",
+    "CIC Plaza Mara Road,",
+    "Upper Hill.",
+];
+
+const DEFAULT_CONTACT: [&str; 4] = [
+    "Tel: 2823000",
+    "Fax: 2823344",
+    "cic.asset@cic.co.ke",
+    "www.cic.co.ke",
+];
+
+/// The CIC block as it has always been printed, with the phone numbers
+/// between the box and street addresses.
+const DEFAULT_BLOCK: [&str; 8] = [
+    "P.O Box: 59485-00200",
+    "Nairobi, Kenya",
+    "Tel: 2823000",
+    "Fax: 2823344",
+    "CIC Plaza Mara Road,",
+    "Upper Hill.",
+    "cic.asset@cic.co.ke",
+    "www.cic.co.ke",
+];
+
+/// Most lines the address block can hold; each line past the default eight
+/// moves the fund title and the table down.
+const MAX_ADDRESS_LINES: usize = 16;
+
+/// The issuing company's look: its logo, the address block in the top right
+/// corner, the table colors and the fonts. Anything left out is the CIC
+/// Asset Management default.
+#[tslink]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Branding {
+    pub logo: Option<Asset>,
+    // multiplies the logo's default size on every page
+    pub logo_scale: Option<f32>,
+    // printed in the top right corner, followed by the contact lines
+    pub address: Option<Vec<String>>,
+    pub contact: Option<Vec<String>>,
+    // hex colors such as "#be0000": table rules and summations, then the
+    // purchases and the sales columns
+    pub primary_color: Option<String>,
+    pub secondary_color: Option<String>,
+    pub accent_color: Option<String>,
+    // used when the payload names no fonts of its own
+    pub fonts: Option<FontConfig>,
+}
+
+/// The colors a statement table is drawn in.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub primary: Rgb,
+    pub secondary: Rgb,
+    pub accent: Rgb,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            primary: Rgb::new(190.0 / 256.0, 0.0 / 256.0, 0.0 / 256.0, None),
+            secondary: Rgb::new(244.0 / 256.0, 164.0 / 256.0, 96.0 / 256.0, None),
+            accent: Rgb::new(87.0 / 256.0, 75.0 / 256.0, 144.0 / 256.0, None),
+        }
+    }
+}
+
+impl Branding {
+    pub fn logo(&self) -> Asset {
+        self.logo
+            .clone()
+            .unwrap_or_else(|| Asset::Path(PathBuf::from(DEFAULT_LOGO)))
+    }
+
+    pub fn logo_scale(&self) -> Result<f32, StatementError> {
+        match self.logo_scale {
+            Some(scale) if !(scale.is_finite() && scale > 0.0) => Err(
+                StatementError::InvalidConfig(format!("invalid logo_scale: {}", scale)),
+            ),
+            scale => Ok(scale.unwrap_or(1.0)),
+        }
+    }
+
+    /// The address block: the address lines, then the contact lines. With
+    /// neither set it is the CIC block in its original order.
+    pub fn address_lines(&self) -> Result<Vec<String>, StatementError> {
+        let lines = |lines: &Option<Vec<String>>, default: &[&str]| match lines {
+            Some(lines) => lines.clone(),
+            None => default.iter().map(|line| line.to_string()).collect(),
+        };
+
+        if self.address.is_none() && self.contact.is_none() {
+            return Ok(lines(&None, &DEFAULT_BLOCK));
+        }
+        let mut address = lines(&self.address, &DEFAULT_ADDRESS);
+        address.extend(lines(&self.contact, &DEFAULT_CONTACT));
+
+        if address.len() > MAX_ADDRESS_LINES {
+            return Err(StatementError::InvalidConfig(format!(
+                "address and contact have {} lines together, at most {} fit",
+                address.len(),
+                MAX_ADDRESS_LINES
+            )));
+        }
+
+        Ok(address)
+    }
+
+    pub fn palette(&self) -> Result<Palette, StatementError> {
+        let default = Palette::default();
+        let color = |name, value: &Option<String>, default| match value {
+            Some(value) => parse_color(name, value),
+            None => Ok(default),
+        };
+
+        Ok(Palette {
+            primary: color("primary_color", &self.primary_color, default.primary)?,
+            secondary: color("secondary_color", &self.secondary_color, default.secondary)?,
+            accent: color("accent_color", &self.accent_color, default.accent)?,
+        })
+    }
+}
+
+/// Reads a `#rrggbb` color; the `#` is optional.
+fn parse_color(name: &str, value: &str) -> Result<Rgb, StatementError> {
+    let invalid = || {
+        StatementError::InvalidConfig(format!(
+            "{} must be a hex color like \"#be0000\", not {:?}",
+            name, value
+        ))
+    };
+
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map(|c| c as f32 / 255.0)
+            .map_err(|_| invalid())
+    };
+
+    Ok(Rgb::new(channel(0)?, channel(2)?, channel(4)?, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(n: usize) -> Option<Vec<String>> {
+        Some((0..n).map(|i| format!("line {}", i)).collect())
+    }
+
+    #[test]
+    fn default_block_keeps_its_order() {
+        let lines = Branding::default().address_lines().unwrap();
+        assert_eq!(lines, DEFAULT_BLOCK);
+    }
+
+    #[test]
+    fn address_block_is_limited() {
+        let branding = Branding {
+            address: lines(12),
+            contact: lines(4),
+            ..Branding::default()
+        };
+        assert_eq!(branding.address_lines().unwrap().len(), MAX_ADDRESS_LINES);
+
+        let branding = Branding {
+            address: lines(13),
+            ..branding
+        };
+        assert!(matches!(
+            branding.address_lines(),
+            Err(StatementError::InvalidConfig(_))
+        ));
+    }
+}
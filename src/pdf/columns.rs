@@ -0,0 +1,459 @@
+use printpdf::Rgb;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tslink::tslink;
+
+use super::{
+    branding::Palette,
+    error::StatementError,
+    payload::{Movement, Transaction},
+    table::{dark, Align, Cell, Column, NumberFormat, Overflow, TABLE_WIDTH},
+};
+
+/// One column of the statement table, as listed in the payload's `columns`.
+/// Only `field` is required; anything left out is taken from how the field
+/// is shown by default.
+#[tslink]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnConfig {
+    pub field: Field,
+    pub title: Option<String>,
+    // in mm; all columns together must fit in 190mm
+    pub width: Option<f32>,
+    pub format: Option<NumberFormat>,
+    // numbers default to decimal alignment and text to the left
+    pub align: Option<Align>,
+    // title shared by neighbouring columns, e.g. "Purchases"
+    pub group: Option<String>,
+    // font size of the column's rows, in points
+    pub size: Option<f32>,
+    // what happens to text too wide for the column; wraps by default
+    pub overflow: Option<Overflow>,
+}
+
+/// What a column shows: a field of the transaction, or an amount split by
+/// which way it moved the account.
+#[tslink]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    TransId,
+    TransDate,
+    TransType,
+    Mop,
+    Amount,
+    Taxamt,
+    Netamount,
+    RunningBalance,
+    RunningShares,
+    Shares,
+    Price,
+    PAmount,
+    WAmount,
+    IAmount,
+    // `amount` under the column for its movement
+    Deposit,
+    Interest,
+    Withdrawal,
+    // units, price and cost of purchases and sales
+    PurchaseUnits,
+    PurchasePrice,
+    PurchaseCost,
+    SaleUnits,
+    SalePrice,
+    SaleCost,
+}
+
+/// What the summation row shows under a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Total {
+    None,
+    Sum,
+    Last,
+}
+
+impl ColumnConfig {
+    pub fn new(field: Field) -> Self {
+        ColumnConfig {
+            field,
+            title: None,
+            width: None,
+            format: None,
+            align: None,
+            group: None,
+            size: None,
+            overflow: None,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// How the column prints its figures, the field's own way unless the
+    /// payload overrides it.
+    pub fn number_format(&self) -> NumberFormat {
+        self.format.unwrap_or_else(|| self.field.format())
+    }
+
+    pub fn column(&self, palette: &Palette) -> Column {
+        let field = self.field;
+        let format = self.number_format();
+        let align = match format {
+            NumberFormat::Text => Align::Left,
+            _ => Align::Decimal,
+        };
+
+        Column {
+            title: self
+                .title
+                .clone()
+                .unwrap_or_else(|| field.title().to_string()),
+            group: self
+                .group
+                .clone()
+                .or_else(|| field.group().map(str::to_string)),
+            width: self.width.unwrap_or_else(|| field.width()),
+            align: self.align.unwrap_or(align),
+            color: field.color(palette),
+            size: self.size,
+            overflow: self.overflow.unwrap_or_default(),
+            format,
+            value: field.value(),
+        }
+    }
+}
+
+impl Field {
+    fn title(self) -> &'static str {
+        match self {
+            Field::TransId => "Trans No.",
+            Field::TransDate => "Trans Date",
+            Field::TransType => "Type",
+            Field::Mop => "Description",
+            Field::Amount => "Amount",
+            Field::Taxamt => "Withholding Tax",
+            Field::Netamount => "Net Amount",
+            Field::RunningBalance => "Running Balance",
+            Field::RunningShares => "Unit Balance",
+            Field::Shares | Field::PurchaseUnits | Field::SaleUnits => "Units",
+            Field::Price | Field::PurchasePrice | Field::SalePrice => "Price",
+            Field::PAmount => "Purchase Amt",
+            Field::WAmount => "Withdrawal Amt",
+            Field::IAmount => "Interest Amt",
+            Field::Deposit => "Deposit",
+            Field::Interest => "Interest",
+            Field::Withdrawal => "Withdrawal",
+            Field::PurchaseCost | Field::SaleCost => "Cost",
+        }
+    }
+
+    fn width(self) -> f32 {
+        match self {
+            Field::TransId => 18.0,
+            Field::TransDate => 19.0,
+            Field::TransType => 22.0,
+            Field::Mop => 32.0,
+            Field::Taxamt => 25.0,
+            Field::RunningBalance => 27.0,
+            _ => 23.0,
+        }
+    }
+
+    fn group(self) -> Option<&'static str> {
+        match self {
+            Field::PurchaseUnits | Field::PurchasePrice | Field::PurchaseCost => Some("Purchases"),
+            Field::SaleUnits | Field::SalePrice | Field::SaleCost => Some("Sales"),
+            _ => None,
+        }
+    }
+
+    fn color(self, palette: &Palette) -> Rgb {
+        match self.group() {
+            Some("Purchases") => palette.secondary.clone(),
+            Some("Sales") => palette.accent.clone(),
+            _ => dark(),
+        }
+    }
+
+    fn format(self) -> NumberFormat {
+        match self {
+            Field::TransId | Field::TransDate | Field::TransType | Field::Mop => NumberFormat::Text,
+            Field::Taxamt => NumberFormat::Tax,
+            Field::RunningBalance => NumberFormat::Balance,
+            Field::RunningShares | Field::Shares | Field::PurchaseUnits | Field::SaleUnits => {
+                NumberFormat::Units
+            }
+            Field::Price | Field::PurchasePrice | Field::SalePrice => NumberFormat::Price,
+            _ => NumberFormat::Amount,
+        }
+    }
+
+    fn total(self) -> Total {
+        match self {
+            Field::TransId
+            | Field::TransDate
+            | Field::TransType
+            | Field::Mop
+            | Field::PurchasePrice
+            | Field::SalePrice => Total::None,
+            // closing figures rather than sums
+            Field::RunningBalance | Field::RunningShares | Field::Price => Total::Last,
+            _ => Total::Sum,
+        }
+    }
+
+    fn value(self) -> fn(&Transaction) -> Cell {
+        match self {
+            Field::TransId => |t| Cell::Text(t.trans_id.to_string()),
+            Field::TransDate => |t| Cell::Text(t.trans_date.format("%Y-%m-%d").to_string()),
+            Field::TransType => |t| Cell::Text(t.trans_type.to_string()),
+            Field::Mop => |t| Cell::Text(t.mop.clone()),
+            Field::Amount => |t| Cell::Number(t.amount),
+            Field::Taxamt => |t| {
+                if t.taxamt.is_zero() {
+                    Cell::Empty
+                } else {
+                    Cell::Number(t.taxamt)
+                }
+            },
+            Field::Netamount => |t| Cell::Number(t.netamount),
+            Field::RunningBalance => |t| Cell::Number(t.running_balance),
+            Field::RunningShares => |t| Cell::Number(t.running_shares),
+            Field::Shares => |t| t.shares.map_or(Cell::Empty, Cell::Number),
+            Field::Price => |t| t.price.map_or(Cell::Empty, Cell::Number),
+            Field::PAmount => |t| Cell::Number(t.p_amount),
+            Field::WAmount => |t| Cell::Number(t.w_amount),
+            Field::IAmount => |t| Cell::Number(t.i_amount),
+            Field::Deposit => |t| amount_if(t, Movement::Deposit),
+            Field::Interest => |t| amount_if(t, Movement::Income),
+            Field::Withdrawal => |t| amount_if(t, Movement::Withdrawal),
+            Field::PurchaseUnits => |t| units_if(t, Movement::Deposit),
+            Field::PurchasePrice => |t| price_if(t, Movement::Deposit),
+            Field::PurchaseCost => |t| amount_if(t, Movement::Deposit),
+            Field::SaleUnits => |t| units_if(t, Movement::Withdrawal),
+            Field::SalePrice => |t| price_if(t, Movement::Withdrawal),
+            Field::SaleCost => |t| amount_if(t, Movement::Withdrawal),
+        }
+    }
+}
+
+/// The smallest difference the statement shows in `field`, as printed by its
+/// column, or by default when no column shows it.
+pub fn printed_step(columns: &[ColumnConfig], field: Field) -> Decimal {
+    columns
+        .iter()
+        .find(|c| c.field == field)
+        .map_or_else(|| field.format(), ColumnConfig::number_format)
+        .step()
+}
+
+/// Rejects column lists that cannot be drawn. Unknown fields are already
+/// refused when the payload is read.
+pub fn check_columns(columns: &[ColumnConfig]) -> Result<(), StatementError> {
+    if columns.is_empty() {
+        return Err(StatementError::InvalidConfig(
+            "columns must list at least one column".to_string(),
+        ));
+    }
+
+    for (i, config) in columns.iter().enumerate() {
+        let sizes = [("width", config.width), ("size", config.size)];
+        for (name, value) in sizes {
+            match value {
+                Some(value) if !(value.is_finite() && value > 0.0) => {
+                    return Err(StatementError::InvalidConfig(format!(
+                        "column {} ({}) has an invalid {}: {}",
+                        i,
+                        serde_json::to_string(&config.field).unwrap_or_default(),
+                        name,
+                        value
+                    )))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let width: f32 = columns
+        .iter()
+        .map(|c| c.width.unwrap_or_else(|| c.field.width()))
+        .sum();
+    if width > TABLE_WIDTH {
+        return Err(StatementError::InvalidConfig(format!(
+            "columns are {}mm wide but the table fits {}mm",
+            width, TABLE_WIDTH
+        )));
+    }
+
+    Ok(())
+}
+
+/// The summation row: sums of the columns that add up and the closing value
+/// of running figures, labelled in the first column when it has no total.
+pub fn totals_row(columns: &[ColumnConfig], rows: &[Transaction]) -> Vec<Cell> {
+    let mut totals: Vec<Cell> = columns
+        .iter()
+        .map(|config| {
+            let value = config.field.value();
+            let mut numbers = rows.iter().filter_map(|t| match value(t) {
+                Cell::Number(n) => Some(n),
+                _ => None,
+            });
+
+            match config.field.total() {
+                Total::None => Cell::Empty,
+                Total::Sum if config.field == Field::Taxamt => Cell::Number(total_tax(rows)),
+                Total::Sum => Cell::Number(numbers.sum()),
+                Total::Last => numbers.next_back().map_or(Cell::Empty, Cell::Number),
+            }
+        })
+        .collect();
+
+    if let Some(first @ Cell::Empty) = totals.first_mut() {
+        *first = Cell::Text("Summations".to_string());
+    }
+
+    totals
+}
+
+/// Withholding tax over all rows, positive however the ledger signs it. The
+/// summation row and the dry-run totals both use it so they always agree.
+pub fn total_tax(rows: &[Transaction]) -> Decimal {
+    rows.iter().map(|t| t.taxamt).sum::<Decimal>().abs()
+}
+
+fn amount_if(t: &Transaction, movement: Movement) -> Cell {
+    if t.movement() == movement {
+        Cell::Number(t.amount.abs())
+    } else {
+        Cell::Empty
+    }
+}
+
+fn units_if(t: &Transaction, movement: Movement) -> Cell {
+    if t.movement() == movement {
+        Cell::Number(t.shares.unwrap_or_default().abs())
+    } else {
+        Cell::Empty
+    }
+}
+
+fn price_if(t: &Transaction, movement: Movement) -> Cell {
+    if t.movement() == movement {
+        Cell::Number(t.price.unwrap_or_default())
+    } else {
+        Cell::Empty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::{check_columns, total_tax, totals_row, ColumnConfig, Field};
+    use crate::pdf::{
+        error::StatementError,
+        fixtures::row,
+        payload::{Transaction, TransactionType},
+        table::Cell,
+    };
+
+    fn rows() -> Vec<Transaction> {
+        let mut interest = row(2, TransactionType::Interest, dec!(10), dec!(1010));
+        interest.taxamt = dec!(-1.5);
+        vec![
+            row(1, TransactionType::Purchase, dec!(1000), dec!(1000)),
+            interest,
+            row(3, TransactionType::Withdrawal, dec!(-200), dec!(808.5)),
+        ]
+    }
+
+    fn invalid(columns: &[ColumnConfig]) -> bool {
+        matches!(
+            check_columns(columns),
+            Err(StatementError::InvalidConfig(_))
+        )
+    }
+
+    #[test]
+    fn columns_must_fit_the_table() {
+        assert!(check_columns(&[ColumnConfig::new(Field::TransDate)]).is_ok());
+        assert!(invalid(&[]));
+        assert!(invalid(&[ColumnConfig::new(Field::Mop).width(0.0)]));
+        assert!(invalid(&[ColumnConfig::new(Field::Mop).size(f32::NAN)]));
+        assert!(invalid(&[
+            ColumnConfig::new(Field::Mop).width(100.0),
+            ColumnConfig::new(Field::Amount).width(91.0),
+        ]));
+        // default widths count too: 9 x 23mm is 207mm
+        assert!(invalid(&vec![ColumnConfig::new(Field::Amount); 9]));
+    }
+
+    #[test]
+    fn totals_sum_amounts_and_close_running_figures() {
+        let columns = [
+            ColumnConfig::new(Field::TransDate),
+            ColumnConfig::new(Field::Deposit),
+            ColumnConfig::new(Field::Interest),
+            ColumnConfig::new(Field::Withdrawal),
+            ColumnConfig::new(Field::Amount),
+            ColumnConfig::new(Field::RunningBalance),
+        ];
+
+        assert_eq!(
+            totals_row(&columns, &rows()),
+            [
+                Cell::Text("Summations".to_string()),
+                Cell::Number(dec!(1000)),
+                Cell::Number(dec!(10)),
+                Cell::Number(dec!(200)),
+                Cell::Number(dec!(810)),
+                Cell::Number(dec!(808.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn totals_label_only_an_empty_first_column() {
+        let columns = [
+            ColumnConfig::new(Field::Deposit),
+            ColumnConfig::new(Field::Mop),
+        ];
+
+        assert_eq!(
+            totals_row(&columns, &rows()),
+            [Cell::Number(dec!(1000)), Cell::Empty]
+        );
+    }
+
+    #[test]
+    fn tax_total_is_positive() {
+        let columns = [
+            ColumnConfig::new(Field::TransId),
+            ColumnConfig::new(Field::Taxamt),
+        ];
+        assert_eq!(total_tax(&rows()), dec!(1.5));
+        assert_eq!(totals_row(&columns, &rows())[1], Cell::Number(dec!(1.5)));
+
+        let mut positive = rows();
+        positive[1].taxamt = dec!(1.5);
+        assert_eq!(total_tax(&positive), dec!(1.5));
+    }
+}
@@ -0,0 +1,110 @@
+use std::{fmt, io, path::PathBuf};
+
+use super::validate::ValidationIssue;
+
+#[derive(Debug)]
+pub enum StatementError {
+    InvalidPayload(String),
+    /// Holds every issue found; at least one of them is an error.
+    Validation(Vec<ValidationIssue>),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Logo {
+        path: PathBuf,
+        source: ::image::ImageError,
+    },
+    Render(String),
+    /// A configured font could not be read as a TrueType or OpenType font.
+    Font(String),
+    InvalidConfig(String),
+    UnsafePath(String),
+}
+
+impl StatementError {
+    /// Stable identifier for callers that branch on the kind of failure. A
+    /// validation failure reports the code of its first error, such as
+    /// `MISSING_FIELD` or `BALANCE_MISMATCH`.
+    pub fn code(&self) -> &str {
+        match self {
+            StatementError::InvalidPayload(_) => "INVALID_PAYLOAD",
+            StatementError::Validation(_) => self.first_issue().map_or("INVALID_DATA", |i| &i.code),
+            StatementError::Io { .. } => "IO_ERROR",
+            StatementError::Logo { .. } => "LOGO_ERROR",
+            StatementError::Render(_) => "RENDER_ERROR",
+            StatementError::Font(_) => "FONT_ERROR",
+            StatementError::InvalidConfig(_) => "INVALID_CONFIG",
+            StatementError::UnsafePath(_) => "UNSAFE_PATH",
+        }
+    }
+
+    pub fn row(&self) -> Option<usize> {
+        self.first_issue().and_then(|issue| issue.row)
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.first_issue().and_then(|issue| issue.field.as_deref())
+    }
+
+    pub fn issues(&self) -> &[ValidationIssue] {
+        match self {
+            StatementError::Validation(issues) => issues,
+            _ => &[],
+        }
+    }
+
+    fn first_issue(&self) -> Option<&ValidationIssue> {
+        self.issues().iter().find(|issue| issue.is_error())
+    }
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatementError::InvalidPayload(msg) => write!(f, "invalid payload: {}", msg),
+            StatementError::Validation(issues) => {
+                let errors = issues.iter().filter(|i| i.is_error()).count();
+                match self.first_issue() {
+                    Some(first) if errors > 1 => {
+                        write!(f, "{} (and {} more errors)", first.message, errors - 1)
+                    }
+                    Some(first) => write!(f, "{}", first.message),
+                    None => write!(f, "invalid statement data"),
+                }
+            }
+            StatementError::Io { path, source } => {
+                write!(f, "io error on {}: {}", path.display(), source)
+            }
+            StatementError::Logo { path, source } => {
+                write!(f, "failed to load logo {}: {}", path.display(), source)
+            }
+            StatementError::Render(msg) => write!(f, "render error: {}", msg),
+            StatementError::Font(msg) => write!(f, "failed to load {}", msg),
+            StatementError::InvalidConfig(msg) => write!(f, "invalid configuration: {}", msg),
+            StatementError::UnsafePath(msg) => write!(f, "unsafe output path: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for StatementError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StatementError::Io { source, .. } => Some(source),
+            StatementError::Logo { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for StatementError {
+    fn from(err: serde_json::Error) -> Self {
+        StatementError::InvalidPayload(err.to_string())
+    }
+}
+
+impl From<printpdf::Error> for StatementError {
+    fn from(err: printpdf::Error) -> Self {
+        StatementError::Render(err.to_string())
+    }
+}
@@ -0,0 +1,75 @@
+//! Transactions and payloads shared by the unit tests.
+
+use chrono::{TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::payload::{Account, Customer, Payload, Transaction, TransactionType, PAYLOAD_VERSION};
+
+/// A money market row dated the `trans_id`th of January 2023.
+pub fn row(
+    trans_id: i64,
+    trans_type: TransactionType,
+    amount: Decimal,
+    running_balance: Decimal,
+) -> Transaction {
+    Transaction {
+        trans_id,
+        member_no: None,
+        account_no: None,
+        trans_date: Utc
+            .with_ymd_and_hms(2023, 1, trans_id as u32, 0, 0, 0)
+            .unwrap(),
+        trans_type,
+        amount,
+        taxamt: Decimal::ZERO,
+        running_balance,
+        running_shares: Decimal::ZERO,
+        shares: None,
+        price: None,
+        netamount: amount,
+        mop: "M-PESA".to_string(),
+        p_amount: Decimal::ZERO,
+        w_amount: Decimal::ZERO,
+        i_amount: Decimal::ZERO,
+    }
+}
+
+/// A balanced fund row moving `shares` units at a price of 10.
+pub fn unit_row(
+    trans_id: i64,
+    trans_type: TransactionType,
+    amount: Decimal,
+    running_balance: Decimal,
+    shares: Decimal,
+    running_shares: Decimal,
+) -> Transaction {
+    Transaction {
+        shares: Some(shares),
+        running_shares,
+        price: Some(dec!(10)),
+        ..row(trans_id, trans_type, amount, running_balance)
+    }
+}
+
+/// A statement for member 00020 with the given rows and nothing configured.
+pub fn payload(transactions: Vec<Transaction>) -> Payload {
+    Payload {
+        version: PAYLOAD_VERSION,
+        pdf_name: "statement".to_string(),
+        customer: Customer {
+            member_no: "00020".to_string(),
+            ..Customer::default()
+        },
+        account: Account {
+            account_no: "001-00020-001".to_string(),
+        },
+        fund: Default::default(),
+        transactions,
+        output: None,
+        rounding: None,
+        columns: None,
+        fonts: None,
+        branding: None,
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use owned_ttf_parser::OwnedFace;
+use printpdf::{BuiltinFont, IndirectFontRef, PdfDocumentReference};
+use serde::Deserialize;
+use tslink::tslink;
+
+use super::{
+    asset::Asset,
+    error::StatementError,
+    metrics::{Face, Metrics},
+};
+
+/// TrueType or OpenType fonts to embed instead of builtin Helvetica, so
+/// names outside the Latin-1 range print. Only the glyphs the statement
+/// uses are embedded.
+#[tslink]
+#[derive(Debug, Clone, Deserialize)]
+pub struct FontConfig {
+    pub regular: Asset,
+    // the regular font is used when left out
+    pub bold: Option<Asset>,
+}
+
+/// A font file read into memory and parsed for its metrics.
+#[derive(Debug, Clone)]
+struct FontFile {
+    bytes: Arc<Vec<u8>>,
+    face: Arc<OwnedFace>,
+}
+
+/// The font files of a [`FontConfig`], one per face.
+#[derive(Debug, Clone)]
+pub struct FontFiles([FontFile; 2]);
+
+/// The fonts a statement is drawn with, one per face.
+#[derive(Debug, Clone)]
+pub struct Fonts([IndirectFontRef; 2]);
+
+impl FontFiles {
+    /// Reads and parses the configured fonts. Without a bold font of its
+    /// own, bold text shares the regular one.
+    pub fn load(config: &FontConfig) -> Result<Self, StatementError> {
+        let regular = load(&config.regular, Face::Regular)?;
+        let bold = match &config.bold {
+            Some(source) => load(source, Face::Bold)?,
+            None => regular.clone(),
+        };
+
+        Ok(FontFiles([regular, bold]))
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics::embedded(self.0.clone().map(|file| file.face))
+    }
+}
+
+impl Fonts {
+    /// Adds the fonts to `doc`: the embedded files when there are any,
+    /// otherwise builtin Helvetica. A file shared by both faces is embedded
+    /// once.
+    pub fn add(
+        doc: &PdfDocumentReference,
+        files: Option<&FontFiles>,
+    ) -> Result<Self, StatementError> {
+        let Some(FontFiles(files)) = files else {
+            return Ok(Fonts([
+                doc.add_builtin_font(BuiltinFont::Helvetica)?,
+                doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+            ]));
+        };
+
+        let mut added: Vec<(Arc<Vec<u8>>, IndirectFontRef)> = vec![];
+        let mut embed = |file: &FontFile| -> Result<IndirectFontRef, StatementError> {
+            let shared = added
+                .iter()
+                .find(|(bytes, _)| Arc::ptr_eq(bytes, &file.bytes));
+            if let Some((_, font)) = shared {
+                return Ok(font.clone());
+            }
+
+            let font = doc.add_external_font(file.bytes.as_slice())?;
+            added.push((file.bytes.clone(), font.clone()));
+            Ok(font)
+        };
+
+        Ok(Fonts([embed(&files[0])?, embed(&files[1])?]))
+    }
+
+    pub fn get(&self, face: Face) -> &IndirectFontRef {
+        &self.0[face as usize]
+    }
+}
+
+/// Reads and parses the font for `face`.
+fn load(asset: &Asset, face: Face) -> Result<FontFile, StatementError> {
+    let bytes = asset.read()?;
+    let parsed = OwnedFace::from_vec(bytes.clone(), 0).map_err(|err| {
+        StatementError::Font(format!(
+            "{} font {}: {}",
+            format!("{:?}", face).to_lowercase(),
+            asset.describe(),
+            err
+        ))
+    })?;
+
+    Ok(FontFile {
+        bytes: Arc::new(bytes),
+        face: Arc::new(parsed),
+    })
+}
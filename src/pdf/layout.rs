@@ -0,0 +1,371 @@
+use std::ops::Range;
+
+use super::{
+    error::StatementError,
+    metrics::{Face, Metrics},
+    payload::Payload,
+    table::Table,
+};
+
+/// Page height in mm; the statement is A4 portrait.
+pub(crate) const PAGE_HEIGHT: f32 = 297.0;
+
+/// Baseline of the footer's last line, and the distance between its lines.
+pub(crate) const FOOTER_BASELINE: f32 = 9.0;
+pub(crate) const FOOTER_LINE_HEIGHT: f32 = 3.0;
+/// Footer text size in points.
+pub(crate) const FOOTER_SIZE: f32 = 6.5;
+/// Left edge and width of the footer: the page less its 10mm margins.
+pub(crate) const FOOTER_X: f32 = 10.0;
+pub(crate) const FOOTER_WIDTH: f32 = 190.0;
+/// Extra space between the footer's paragraphs.
+const PARAGRAPH_GAP: f32 = 1.5;
+/// Space kept clear between the table and the top of the footer.
+const FOOTER_CLEARANCE: f32 = 4.0;
+
+/// A run of footer text set in one face, with its measured width in mm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub face: Face,
+    pub width: f32,
+}
+
+/// One wrapped footer line, with its measured width and baseline in mm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FooterLine {
+    pub spans: Vec<Span>,
+    pub width: f32,
+    pub y: f32,
+}
+
+/// The disclaimer as printed at the foot of every page. Each line of the
+/// disclaimer starts a paragraph and text between `**` is set in bold.
+/// Paragraphs wrap by measured width and every line is centered.
+#[derive(Debug, Clone, Default)]
+pub struct Footer {
+    pub lines: Vec<FooterLine>,
+}
+
+impl Footer {
+    pub fn new(disclaimer: &str, metrics: &Metrics) -> Self {
+        let paragraphs: Vec<Vec<Vec<Span>>> = disclaimer
+            .lines()
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| wrap_spans(&words(p), metrics))
+            .collect();
+
+        // laid out upwards from the baseline, last paragraph first
+        let mut lines = vec![];
+        let mut y = FOOTER_BASELINE;
+        for paragraph in paragraphs.into_iter().rev() {
+            for spans in paragraph.into_iter().rev() {
+                let width = spans.iter().map(|span| span.width).sum();
+                lines.push(FooterLine { spans, width, y });
+                y += FOOTER_LINE_HEIGHT;
+            }
+            y += PARAGRAPH_GAP;
+        }
+        lines.reverse();
+
+        Footer { lines }
+    }
+
+    /// Height from the bottom of the page that the footer occupies,
+    /// including the gap kept above it.
+    pub fn height(&self) -> f32 {
+        let top = self.lines.first().map_or(FOOTER_BASELINE, |line| line.y);
+
+        top + FOOTER_CLEARANCE
+    }
+}
+
+impl FooterLine {
+    /// Left edge of the line so it sits centered in the footer.
+    pub fn x(&self) -> f32 {
+        FOOTER_X + (FOOTER_WIDTH - self.width).max(0.0) / 2.0
+    }
+}
+
+/// Splits a paragraph into words, each a list of runs since a word can
+/// change face midway, as in `**Note**:`. Only paired `**` switch to bold;
+/// an unmatched `**` and single asterisks are printed as they are.
+fn words(paragraph: &str) -> Vec<Vec<(String, Face)>> {
+    let mut parts: Vec<String> = paragraph.split("**").map(str::to_string).collect();
+    // an even number of parts means the last `**` has no partner
+    if parts.len() % 2 == 0 {
+        let last = parts.pop().unwrap_or_default();
+        if let Some(before) = parts.last_mut() {
+            before.push_str("**");
+            before.push_str(&last);
+        }
+    }
+
+    let mut words: Vec<Vec<(String, Face)>> = vec![];
+    let mut space = true;
+
+    for (i, part) in parts.iter().enumerate() {
+        let face = if i % 2 == 1 {
+            Face::Bold
+        } else {
+            Face::Regular
+        };
+
+        for (j, piece) in part.split(char::is_whitespace).enumerate() {
+            space = space || j > 0;
+            if piece.is_empty() {
+                continue;
+            }
+
+            let piece = (piece.to_string(), face);
+            match words.last_mut() {
+                Some(word) if !space => word.push(piece),
+                _ => words.push(vec![piece]),
+            }
+            space = false;
+        }
+    }
+
+    words
+}
+
+/// Fills lines word by word up to the footer width. A word wider than the
+/// footer gets a line of its own.
+fn wrap_spans(words: &[Vec<(String, Face)>], metrics: &Metrics) -> Vec<Vec<Span>> {
+    let mut lines = vec![];
+    let mut line: Vec<Span> = vec![];
+    let line_width = |spans: &[Span]| spans.iter().map(|span| span.width).sum::<f32>();
+
+    for word in words {
+        let start = line.is_empty();
+        let mut candidate = line.clone();
+        for (i, (text, face)) in word.iter().enumerate() {
+            let text = if i == 0 && !start {
+                format!(" {}", text)
+            } else {
+                text.clone()
+            };
+            match candidate.last_mut() {
+                Some(last) if last.face == *face => last.text.push_str(&text),
+                _ => candidate.push(Span {
+                    text,
+                    face: *face,
+                    width: 0.0,
+                }),
+            }
+        }
+        for span in &mut candidate {
+            span.width = metrics.text_width(&span.text, span.face, FOOTER_SIZE);
+        }
+
+        if start || line_width(&candidate) <= FOOTER_WIDTH {
+            line = candidate;
+        } else {
+            let word = word.iter().map(|(text, face)| Span {
+                text: text.clone(),
+                face: *face,
+                width: metrics.text_width(text, *face, FOOTER_SIZE),
+            });
+            lines.push(std::mem::replace(&mut line, word.collect()));
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Splits the rows into pages by measuring them against the space between
+/// the table's top and the footer. The summation block goes on the last
+/// page; if it doesn't fit, the last row moves to a new page with it so it
+/// never stands alone. When a tall header leaves no room for the first row,
+/// or for a lone row and the summation block, the first page is left empty
+/// and the table starts on the second.
+pub fn paginate(payload: &Payload, table: &Table) -> Result<Vec<Range<usize>>, StatementError> {
+    let rounding = payload.rounding.unwrap_or_default();
+    let bottom = Footer::new(&payload.fund.disclaimer, &table.metrics).height();
+    let heights: Vec<f32> = payload
+        .transactions
+        .iter()
+        .map(|t| table.row_height(t, &rounding))
+        .collect();
+    let room = |page: usize| {
+        let top = if page == 0 {
+            table.top_first
+        } else {
+            table.top
+        };
+        top - bottom - table.header_height()
+    };
+
+    let too_tall =
+        |row: usize| StatementError::Render(format!("row {} is too tall to fit on a page", row));
+
+    let mut pages = vec![];
+    let mut start = 0;
+    let mut used = 0.0;
+    for (row, height) in heights.iter().enumerate() {
+        if used + height > room(pages.len()) {
+            if row == start && !pages.is_empty() {
+                return Err(too_tall(row));
+            }
+            pages.push(start..row);
+            start = row;
+            used = 0.0;
+            if *height > room(pages.len()) {
+                return Err(too_tall(row));
+            }
+        }
+        used += height;
+    }
+
+    if used + table.summary_height() > room(pages.len()) {
+        if heights.len() - start > 1 {
+            let last = heights.len() - 1;
+            pages.push(start..last);
+            start = last;
+            used = heights[last];
+        } else if pages.is_empty() {
+            pages.push(start..start);
+        }
+    }
+    if used + table.summary_height() > room(pages.len()) {
+        return Err(StatementError::Render(
+            "the summation block does not fit on the last page".to_string(),
+        ));
+    }
+    pages.push(start..heights.len());
+
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::{paginate, words, Footer};
+    use crate::pdf::{
+        branding::Palette,
+        columns::{ColumnConfig, Field},
+        error::StatementError,
+        fixtures::{payload, row},
+        metrics::{
+            Face::{self, Bold, Regular},
+            Metrics,
+        },
+        payload::{Payload, TransactionType},
+        table::Table,
+    };
+
+    fn word(runs: &[(&str, Face)]) -> Vec<(String, Face)> {
+        runs.iter()
+            .map(|(text, face)| (text.to_string(), *face))
+            .collect()
+    }
+
+    #[test]
+    fn paired_stars_set_bold() {
+        assert_eq!(
+            words("**Note**: see **all terms**"),
+            [
+                word(&[("Note", Bold), (":", Regular)]),
+                word(&[("see", Regular)]),
+                word(&[("all", Bold)]),
+                word(&[("terms", Bold)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_stars_are_text() {
+        assert_eq!(
+            words("*Past returns. Rates 5 * 3."),
+            [
+                word(&[("*Past", Regular)]),
+                word(&[("returns.", Regular)]),
+                word(&[("Rates", Regular)]),
+                word(&[("5", Regular)]),
+                word(&[("*", Regular)]),
+                word(&[("3.", Regular)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmatched_double_stars_are_text() {
+        assert_eq!(
+            words("**Bold** and a ** b"),
+            [
+                word(&[("Bold", Bold)]),
+                word(&[("and", Regular)]),
+                word(&[("a", Regular)]),
+                word(&[("**", Regular)]),
+                word(&[("b", Regular)]),
+            ]
+        );
+        assert_eq!(words("fees**"), [word(&[("fees**", Regular)])]);
+    }
+
+    /// `count` one-line rows.
+    fn rows(count: i64) -> Payload {
+        let rows = (1..=count)
+            .map(|i| row(i, TransactionType::Purchase, dec!(1), i.into()))
+            .collect();
+        payload(rows)
+    }
+
+    /// A table with room for `first` rows on the first page and `rest` on
+    /// the others.
+    fn table(first: usize, rest: usize) -> Table {
+        let table = Table::new(vec![
+            ColumnConfig::new(Field::TransDate).column(&Palette::default())
+        ]);
+        let chrome = Footer::new("", &Metrics::default()).height() + table.header_height();
+        Table {
+            top_first: chrome + first as f32 * table.row_height,
+            top: chrome + rest as f32 * table.row_height,
+            ..table
+        }
+    }
+
+    #[test]
+    fn rows_fill_each_page() {
+        assert_eq!(
+            paginate(&rows(10), &table(3, 4)).unwrap(),
+            [0..3, 3..7, 7..10]
+        );
+        let pages = paginate(&rows(2), &table(3, 4)).unwrap();
+        assert_eq!((pages.len(), pages[0].clone()), (1, 0..2));
+    }
+
+    #[test]
+    fn last_row_moves_with_the_summation() {
+        // seven rows leave page two full, with no room for the summation
+        assert_eq!(
+            paginate(&rows(7), &table(3, 4)).unwrap(),
+            [0..3, 3..6, 6..7]
+        );
+    }
+
+    #[test]
+    fn table_starts_on_page_two_under_a_tall_header() {
+        assert_eq!(
+            paginate(&rows(5), &table(0, 4)).unwrap(),
+            [0..0, 0..4, 4..5]
+        );
+        // a lone row fits but the summation doesn't
+        assert_eq!(paginate(&rows(1), &table(1, 4)).unwrap(), [0..0, 0..1]);
+    }
+
+    #[test]
+    fn rows_taller_than_a_page_fail() {
+        for (first, rest) in [(0, 0), (2, 0)] {
+            assert!(matches!(
+                paginate(&rows(3), &table(first, rest)),
+                Err(StatementError::Render(_))
+            ));
+        }
+    }
+}
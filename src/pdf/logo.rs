@@ -0,0 +1,259 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use ::image::{guess_format, load_from_memory, ImageError, ImageFormat};
+use printpdf::{
+    lopdf::{Document, Object, ObjectId},
+    ColorBits, ColorSpace, Image, ImageFilter, ImageXObject, Px,
+};
+use sha2::{Digest, Sha256};
+
+use super::{asset::Asset, error::StatementError};
+
+/// A logo ready to embed. JPEG files keep their compressed data, which the
+/// PDF viewer decodes itself; other formats are decoded to plain RGB,
+/// dropping transparency.
+#[derive(Debug)]
+pub struct Logo {
+    width: usize,
+    height: usize,
+    color_space: ColorSpace,
+    data: Vec<u8>,
+    filter: Option<ImageFilter>,
+}
+
+/// The logo in `asset`. A logo file is read and decoded the first time the
+/// process asks for it and kept by path, so a file replaced on disk is only
+/// picked up after a restart. Logos given as bytes are decoded on every call
+/// and never kept, so callers passing a new buffer per render do not grow
+/// the cache.
+pub fn load_logo(asset: &Asset) -> Result<Arc<Logo>, StatementError> {
+    static LOGOS: OnceLock<Mutex<HashMap<PathBuf, Arc<Logo>>>> = OnceLock::new();
+
+    let Asset::Path(path) = asset else {
+        return Ok(Arc::new(Logo::read(asset)?));
+    };
+    let logos = LOGOS.get_or_init(Default::default);
+    if let Some(logo) = logos.lock().unwrap().get(path) {
+        return Ok(logo.clone());
+    }
+
+    // decoded outside the lock; renders racing for a new logo each decode it
+    let logo = Arc::new(Logo::read(asset)?);
+    logos.lock().unwrap().insert(path.clone(), logo.clone());
+
+    Ok(logo)
+}
+
+impl Logo {
+    fn read(asset: &Asset) -> Result<Self, StatementError> {
+        let error = |source| StatementError::Logo {
+            path: PathBuf::from(asset.describe()),
+            source,
+        };
+        let bytes = match asset {
+            Asset::Path(path) => fs::read(path).map_err(|err| error(ImageError::IoError(err)))?,
+            Asset::Bytes(data) | Asset::Buffer { data } => data.clone(),
+        };
+
+        // decoded even when passed through, so a broken file fails here
+        let image = load_from_memory(&bytes).map_err(error)?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+
+        let jpeg = guess_format(&bytes).is_ok_and(|format| format == ImageFormat::Jpeg);
+        let color_space = match jpeg_components(&bytes) {
+            Some(1) if jpeg => Some(ColorSpace::Greyscale),
+            Some(3) if jpeg => Some(ColorSpace::Rgb),
+            _ => None,
+        };
+        if let Some(color_space) = color_space {
+            return Ok(Logo {
+                width,
+                height,
+                color_space,
+                data: bytes,
+                filter: Some(ImageFilter::DCT),
+            });
+        }
+
+        Ok(Logo {
+            width,
+            height,
+            color_space: ColorSpace::Rgb,
+            data: image.into_rgb8().into_raw(),
+            filter: None,
+        })
+    }
+
+    pub fn image(&self) -> Image {
+        Image::from(ImageXObject {
+            width: Px(self.width),
+            height: Px(self.height),
+            color_space: self.color_space,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: self.data.clone(),
+            image_filter: self.filter,
+            clipping_bbox: None,
+        })
+    }
+}
+
+/// Color components of an 8-bit JPEG, read from its frame header. CMYK and
+/// other layouts are decoded rather than passed through.
+fn jpeg_components(data: &[u8]) -> Option<u8> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut i = 2;
+    while i + 9 < data.len() {
+        if data[i] != 0xFF {
+            return None;
+        }
+        let marker = data[i + 1];
+        let length = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        match marker {
+            // padding before a marker
+            0xFF => i += 1,
+            // baseline, extended and progressive frames
+            0xC0..=0xC2 => return (data[i + 4] == 8).then_some(data[i + 9]),
+            _ => i += 2 + length,
+        }
+    }
+
+    None
+}
+
+/// printpdf gives every page its own copy of an image. Rewrites the saved
+/// document so all pages share the first copy of each image and the others
+/// are dropped.
+pub fn share_images(pdf: Vec<u8>) -> Result<Vec<u8>, StatementError> {
+    let render_error = |err: printpdf::lopdf::Error| StatementError::Render(err.to_string());
+    let mut doc = Document::load_mem(&pdf).map_err(render_error)?;
+
+    let mut first: HashMap<[u8; 32], ObjectId> = HashMap::new();
+    let mut copies: HashMap<ObjectId, ObjectId> = HashMap::new();
+    for (&id, object) in &doc.objects {
+        let Object::Stream(stream) = object else {
+            continue;
+        };
+        if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Image") {
+            continue;
+        }
+
+        let mut digest = Sha256::new();
+        digest.update(format!("{:?}", stream.dict));
+        digest.update(&stream.content);
+        match first.entry(digest.finalize().into()) {
+            Entry::Occupied(entry) => {
+                copies.insert(id, *entry.get());
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(id);
+            }
+        }
+    }
+
+    if copies.is_empty() {
+        return Ok(pdf);
+    }
+    for object in doc.objects.values_mut() {
+        repoint(object, &copies);
+    }
+    for id in copies.keys() {
+        doc.objects.remove(id);
+    }
+
+    let mut bytes = Vec::with_capacity(pdf.len());
+    doc.save_to(&mut bytes)
+        .map_err(|err| StatementError::Render(err.to_string()))?;
+
+    Ok(bytes)
+}
+
+fn repoint(object: &mut Object, copies: &HashMap<ObjectId, ObjectId>) {
+    match object {
+        Object::Reference(id) => {
+            if let Some(first) = copies.get(id) {
+                *id = *first;
+            }
+        }
+        Object::Array(items) => items.iter_mut().for_each(|item| repoint(item, copies)),
+        Object::Dictionary(dict) => dict.iter_mut().for_each(|(_, item)| repoint(item, copies)),
+        Object::Stream(stream) => stream
+            .dict
+            .iter_mut()
+            .for_each(|(_, item)| repoint(item, copies)),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use printpdf::lopdf::{Document, Object};
+    use rust_decimal_macros::dec;
+
+    use super::jpeg_components;
+    use crate::pdf::{
+        fixtures::{payload, row},
+        payload::TransactionType,
+        util::render_pdf,
+    };
+
+    /// The markers of a JPEG up to its frame header, after an APP0 segment.
+    fn jpeg(frame: u8, precision: u8, components: u8) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00];
+        data.extend([0xFF, frame, 0x00, 0x0B, precision, 0x00, 0x10, 0x00, 0x10]);
+        data.extend([components, 0x01, 0x11, 0x00, 0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn reads_components_from_the_frame_header() {
+        assert_eq!(jpeg_components(&jpeg(0xC0, 8, 1)), Some(1));
+        assert_eq!(jpeg_components(&jpeg(0xC0, 8, 3)), Some(3));
+        assert_eq!(jpeg_components(&jpeg(0xC2, 8, 4)), Some(4));
+
+        let mut padded = jpeg(0xC1, 8, 3);
+        padded.insert(8, 0xFF);
+        assert_eq!(jpeg_components(&padded), Some(3));
+    }
+
+    #[test]
+    fn other_images_have_no_components() {
+        // 12-bit samples
+        assert_eq!(jpeg_components(&jpeg(0xC0, 12, 3)), None);
+        // no frame before the data ends
+        assert_eq!(jpeg_components(&jpeg(0xC0, 8, 3)[..8]), None);
+        assert_eq!(jpeg_components(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), None);
+    }
+
+    #[test]
+    fn pages_share_one_logo() {
+        let rows = (1..=31)
+            .map(|i| row(i, TransactionType::Purchase, dec!(1), i.into()))
+            .collect();
+        let mut payload = payload(rows);
+        payload.fund.disclaimer = "Terms apply.\n".repeat(30);
+
+        let rendered = render_pdf(&payload, true).unwrap();
+        assert!(rendered.pages > 1);
+
+        let doc = Document::load_mem(&rendered.bytes).unwrap();
+        let images = doc
+            .objects
+            .values()
+            .filter_map(|object| match object {
+                Object::Stream(stream) => stream.dict.get(b"Subtype").ok(),
+                _ => None,
+            })
+            .filter(|subtype| subtype.as_name().ok() == Some(b"Image"))
+            .count();
+        assert_eq!(images, 1);
+    }
+}
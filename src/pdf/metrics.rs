@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use owned_ttf_parser::{AsFaceRef, GlyphId, OwnedFace};
+
+/// Advance widths of the builtin Helvetica faces in 1/1000 em, for the
+/// printable ASCII range starting at the space (Adobe AFM metrics).
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 222, 333, 333, 389, 584, 278, 333, 278, 278, // ' '../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // 0..?
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // P.._
+    222, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // `..o
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // p..~
+];
+
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 278, 333, 333, 389, 584, 278, 333, 278, 278, // ' '../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, // 0..?
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556, // P.._
+    278, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611, // `..o
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584, // p..~
+];
+
+/// Width used for characters outside the table, roughly an average glyph.
+const FALLBACK_WIDTH: u16 = 556;
+
+pub(crate) const MM_PER_PT: f32 = 25.4 / 72.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    Regular,
+    Bold,
+}
+
+/// Measures text the way it will be set: with the glyph advances of the
+/// embedded fonts when the statement has them, or of builtin Helvetica.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    embedded: Option<[Arc<OwnedFace>; 2]>,
+}
+
+impl Metrics {
+    /// Metrics of embedded fonts, given as regular and bold.
+    pub fn embedded(faces: [Arc<OwnedFace>; 2]) -> Self {
+        Metrics {
+            embedded: Some(faces),
+        }
+    }
+
+    /// Width of `text` in mm when set in `face` at `size` points.
+    pub fn text_width(&self, text: &str, face: Face, size: f32) -> f32 {
+        let em = match &self.embedded {
+            Some(faces) => embedded_width(faces[face as usize].as_face_ref(), text),
+            None => builtin_width(text, face),
+        };
+
+        em * size * MM_PER_PT
+    }
+
+    /// Breaks `text` into lines no wider than `max_width` mm, splitting on
+    /// whitespace and, for words that are too long on their own, between
+    /// characters. Always returns at least one line.
+    pub fn wrap(&self, text: &str, face: Face, size: f32, max_width: f32) -> Vec<String> {
+        let fits = |s: &str| self.text_width(s, face, size) <= max_width;
+        let mut lines = vec![];
+        let mut line = String::new();
+
+        for word in text.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+
+            if fits(&candidate) {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            for c in word.chars() {
+                line.push(c);
+                if !fits(&line) && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::take(&mut line));
+                    line.push(c);
+                }
+            }
+        }
+
+        if !line.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    /// `text` cut short with `...` so it fits `max_width` mm.
+    pub fn truncate(&self, text: &str, face: Face, size: f32, max_width: f32) -> String {
+        if self.text_width(text, face, size) <= max_width {
+            return text.to_string();
+        }
+
+        let mut cut = String::new();
+        for c in text.chars() {
+            cut.push(c);
+            if self.text_width(&format!("{}...", cut), face, size) > max_width {
+                cut.pop();
+                break;
+            }
+        }
+
+        format!("{}...", cut.trim_end())
+    }
+
+    /// The size, at most `size` and in tenths of a point, at which `text`
+    /// fits `max_width` mm, but no smaller than `min_size`.
+    pub fn shrink(&self, text: &str, face: Face, size: f32, max_width: f32, min_size: f32) -> f32 {
+        let width = self.text_width(text, face, size);
+        if width <= max_width {
+            return size;
+        }
+
+        let fitted = (size * max_width / width * 10.0).floor() / 10.0;
+        fitted.max(min_size.min(size))
+    }
+
+    /// Fits `text` on one line: shrinks it down to [`MIN_SIZE`], then
+    /// truncates whatever still doesn't fit. Returns the text and the size
+    /// to set it at.
+    pub fn fit_line(&self, text: &str, face: Face, size: f32, max_width: f32) -> (String, f32) {
+        let size = self.shrink(text, face, size, max_width, MIN_SIZE);
+
+        (self.truncate(text, face, size, max_width), size)
+    }
+}
+
+/// Smallest size text is shrunk to before it is truncated instead.
+pub(crate) const MIN_SIZE: f32 = 5.0;
+
+/// Width in ems from the builtin tables.
+fn builtin_width(text: &str, face: Face) -> f32 {
+    let widths = match face {
+        Face::Regular => &HELVETICA,
+        Face::Bold => &HELVETICA_BOLD,
+    };
+
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            (c as usize)
+                .checked_sub(32)
+                .and_then(|i| widths.get(i))
+                .copied()
+                .unwrap_or(FALLBACK_WIDTH) as u32
+        })
+        .sum();
+
+    units as f32 / 1000.0
+}
+
+/// Width in ems from the font's own advances. Characters the font lacks
+/// are set with its missing glyph, so they are measured as that.
+fn embedded_width(font: &owned_ttf_parser::Face, text: &str) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            let glyph = font.glyph_index(c).unwrap_or(GlyphId(0));
+            font.glyph_hor_advance(glyph).unwrap_or(0) as u32
+        })
+        .sum();
+
+    units as f32 / font.units_per_em().unwrap_or(1000) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Face, Metrics, MIN_SIZE};
+
+    /// Width in mm of `text` in regular Helvetica at `size` points.
+    fn width(text: &str, size: f32) -> f32 {
+        Metrics::default().text_width(text, Face::Regular, size)
+    }
+
+    #[test]
+    fn wraps_between_words_at_the_width() {
+        let metrics = Metrics::default();
+        let wrap = |text, max_width| metrics.wrap(text, Face::Regular, 10.0, max_width);
+
+        assert_eq!(
+            wrap("1111 2222 3333", width("1111 2222", 10.0)),
+            ["1111 2222", "3333"]
+        );
+        assert_eq!(
+            wrap("1111 2222 3333", width("1111 222", 10.0)),
+            ["1111", "2222", "3333"]
+        );
+        assert_eq!(wrap("", 10.0), [""]);
+    }
+
+    #[test]
+    fn splits_words_too_wide_on_their_own() {
+        let wrap =
+            Metrics::default().wrap("12 1234567890", Face::Regular, 10.0, width("1234", 10.0));
+
+        assert_eq!(wrap, ["12", "1234", "5678", "90"]);
+    }
+
+    #[test]
+    fn truncates_with_an_ellipsis() {
+        let metrics = Metrics::default();
+        let truncate = |text, max_width| metrics.truncate(text, Face::Regular, 10.0, max_width);
+
+        assert_eq!(truncate("1234567890", width("1234...", 10.0)), "1234...");
+        // the space before the cut is dropped
+        assert_eq!(truncate("1234 67890", width("1234 ...", 10.0)), "1234...");
+        assert_eq!(truncate("1234", width("1234", 10.0)), "1234");
+    }
+
+    #[test]
+    fn shrinks_to_fit_in_tenths_of_a_point() {
+        let metrics = Metrics::default();
+        let fit = |max_width| metrics.fit_line("1234567890", Face::Regular, 10.0, max_width);
+
+        assert_eq!(
+            fit(width("1234567890", 10.0)),
+            ("1234567890".to_string(), 10.0)
+        );
+        assert_eq!(
+            fit(width("1234567890", 8.05)),
+            ("1234567890".to_string(), 8.0)
+        );
+    }
+
+    #[test]
+    fn truncates_below_the_minimum_size() {
+        let metrics = Metrics::default();
+        let max_width = width("1234...", MIN_SIZE);
+
+        assert_eq!(
+            metrics.fit_line("1234567890", Face::Regular, 10.0, max_width),
+            ("1234...".to_string(), MIN_SIZE)
+        );
+        // text already set smaller than the minimum keeps its size
+        assert_eq!(
+            metrics.shrink("1234567890", Face::Regular, 4.0, 1.0, MIN_SIZE),
+            4.0
+        );
+    }
+}
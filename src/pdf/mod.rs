@@ -1 +1,19 @@
+pub mod asset;
+pub mod batch;
+pub mod branding;
+pub mod columns;
+pub mod error;
+#[cfg(test)]
+mod fixtures;
+pub mod fonts;
+pub mod layout;
+pub mod logo;
+pub mod metrics;
+pub mod output;
+pub mod payload;
+pub mod pool;
+pub mod rounding;
+pub mod summary;
+pub mod table;
 pub mod util;
+pub mod validate;
@@ -0,0 +1,543 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Component, Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        RwLock,
+    },
+};
+
+use serde::Deserialize;
+use tslink::tslink;
+
+use super::error::StatementError;
+
+/// Directory statements are written under until the process sets another.
+const DEFAULT_ROOT: &str = "storage";
+
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the directory every statement is written under. The root is process
+/// configuration and never comes from a payload, so callers can only choose
+/// paths below it.
+pub fn set_root(root: PathBuf) {
+    *ROOT.write().unwrap() = Some(root);
+}
+
+pub fn root() -> PathBuf {
+    ROOT.read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_ROOT))
+}
+
+/// How generated statements are written below the output root. Every field
+/// is optional in the payload; the defaults write
+/// `{member_no}/{pdf_name}.pdf` and its `.json` manifest.
+#[tslink]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    // path below the output root; supports {member_no}, {account_no},
+    // {security_code}, {period_end} and {pdf_name}
+    pub template: String,
+    pub create_dirs: bool,
+    pub overwrite: OverwritePolicy,
+    // write a .json manifest next to each PDF
+    pub manifest: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig {
+            template: "{member_no}/{pdf_name}.pdf".to_string(),
+            create_dirs: true,
+            overwrite: OverwritePolicy::Overwrite,
+            manifest: true,
+        }
+    }
+}
+
+#[tslink]
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverwritePolicy {
+    // refuse to replace an existing statement
+    Fail,
+    #[default]
+    Overwrite,
+    // keep the existing file and write name-1.pdf, name-2.pdf, ...
+    Versioned,
+}
+
+/// Values substituted into [`OutputConfig::template`].
+pub struct FileNameParts<'a> {
+    pub member_no: &'a str,
+    pub account_no: &'a str,
+    pub security_code: &'a str,
+    pub period_end: String,
+    pub pdf_name: &'a str,
+}
+
+impl OutputConfig {
+    pub fn path_for(&self, root: &Path, parts: &FileNameParts) -> Result<PathBuf, StatementError> {
+        let mut name = String::new();
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find('{') {
+            name.push_str(&rest[..start]);
+            let end = rest[start..].find('}').ok_or_else(|| {
+                StatementError::InvalidConfig(format!(
+                    "unclosed placeholder in template `{}`",
+                    self.template
+                ))
+            })? + start;
+
+            let key = &rest[start + 1..end];
+            let value = match key {
+                "member_no" => parts.member_no,
+                "account_no" => parts.account_no,
+                "security_code" => parts.security_code,
+                "period_end" => &parts.period_end,
+                "pdf_name" => parts.pdf_name,
+                other => {
+                    return Err(StatementError::InvalidConfig(format!(
+                        "unknown placeholder `{{{}}}` in template",
+                        other
+                    )))
+                }
+            };
+            check_component(key, value)?;
+            name.push_str(value);
+            rest = &rest[end + 1..];
+        }
+        name.push_str(rest);
+
+        let relative = Path::new(&name);
+        let escapes = relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes || relative.file_name().is_none() {
+            return Err(StatementError::UnsafePath(format!(
+                "`{}` must be a relative file path without `..`",
+                name
+            )));
+        }
+
+        Ok(root.join(relative))
+    }
+
+    /// Writes `bytes` to `path` below `root`, honouring the overwrite policy,
+    /// and returns the path actually written. The PDF is written and synced
+    /// to a temporary file next to the target and only then moved into place,
+    /// so readers never see a partial statement.
+    pub fn write(
+        &self,
+        root: &Path,
+        path: PathBuf,
+        bytes: &[u8],
+    ) -> Result<PathBuf, StatementError> {
+        let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        if self.create_dirs {
+            create_dirs(root)?;
+        }
+        let root = canonical(root)?;
+
+        if self.create_dirs {
+            // checked before creating anything, so a symlink under the root
+            // cannot get directories made outside it
+            check_inside_root(&root, existing_ancestor(&dir))?;
+            create_dirs(&dir)?;
+        }
+        check_inside_root(&root, &dir)?;
+
+        let temp = TempFile::write(&path, bytes)?;
+
+        let path = match self.overwrite {
+            OverwritePolicy::Overwrite => temp.replace(path)?,
+            OverwritePolicy::Fail => temp.link(path)?,
+            OverwritePolicy::Versioned => temp.link_versioned(path)?,
+        };
+
+        sync_dir(&dir);
+
+        Ok(path)
+    }
+
+    /// Writes `bytes` next to the PDF at `pdf`, with the same name and a
+    /// `.json` extension. The PDF's name is already settled by the
+    /// overwrite policy, so the manifest always replaces its old copy.
+    pub fn write_manifest(&self, pdf: &Path, bytes: &[u8]) -> Result<PathBuf, StatementError> {
+        write_replacing(pdf.with_extension("json"), bytes)
+    }
+}
+
+/// Writes `bytes` to `path` through a synced temporary file, atomically
+/// replacing whatever is there.
+pub fn write_replacing(path: PathBuf, bytes: &[u8]) -> Result<PathBuf, StatementError> {
+    let path = TempFile::write(&path, bytes)?.replace(path)?;
+    sync_dir(path.parent().unwrap_or(Path::new("")));
+
+    Ok(path)
+}
+
+/// Placeholder values come straight from the payload, so each must stay a
+/// single plain path component.
+fn check_component(key: &str, value: &str) -> Result<(), StatementError> {
+    let unsafe_char = value
+        .chars()
+        .any(|c| c == '/' || c == '\\' || c.is_control());
+
+    if value.is_empty() || value == "." || value == ".." || unsafe_char {
+        return Err(StatementError::UnsafePath(format!(
+            "`{}` value {:?} is not a safe file name component",
+            key, value
+        )));
+    }
+
+    Ok(())
+}
+
+/// Resolves symlinks so a link planted under the root cannot redirect the
+/// write elsewhere. `root` must already be canonical.
+fn check_inside_root(root: &Path, dir: &Path) -> Result<(), StatementError> {
+    let dir = canonical(dir)?;
+
+    if !dir.starts_with(root) {
+        return Err(StatementError::UnsafePath(format!(
+            "{} is outside the output root {}",
+            dir.display(),
+            root.display()
+        )));
+    }
+
+    Ok(())
+}
+
+/// The deepest part of `dir` that exists already. A dangling symlink counts
+/// as existing, so resolving it fails instead of being created through.
+fn existing_ancestor(dir: &Path) -> &Path {
+    dir.ancestors()
+        .find(|path| fs::symlink_metadata(or_current_dir(path)).is_ok())
+        .unwrap_or(Path::new(""))
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, StatementError> {
+    let path = or_current_dir(path);
+    path.canonicalize().map_err(|source| StatementError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn create_dirs(dir: &Path) -> Result<(), StatementError> {
+    fs::create_dir_all(or_current_dir(dir)).map_err(|source| StatementError::Io {
+        path: dir.to_path_buf(),
+        source,
+    })
+}
+
+/// Persists a rename into `dir`; not every platform can open a directory.
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(or_current_dir(dir)) {
+        let _ = dir.sync_all();
+    }
+}
+
+fn or_current_dir(path: &Path) -> &Path {
+    if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    }
+}
+
+/// A synced temporary file in the target's directory, removed on drop
+/// unless it was renamed into place.
+struct TempFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl TempFile {
+    fn write(target: &Path, bytes: &[u8]) -> Result<Self, StatementError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let path = target.with_file_name(format!(
+            ".{}.{}-{}.tmp",
+            name,
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|source| StatementError::Io {
+                path: path.clone(),
+                source,
+            })?;
+        let temp = TempFile {
+            path,
+            persisted: false,
+        };
+
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(bytes)
+            .and_then(|_| writer.flush())
+            .and_then(|_| writer.get_ref().sync_all())
+            .map_err(|source| temp.io_error(source))?;
+
+        Ok(temp)
+    }
+
+    /// Atomically replaces whatever is at `target`.
+    fn replace(mut self, target: PathBuf) -> Result<PathBuf, StatementError> {
+        fs::rename(&self.path, &target).map_err(|source| StatementError::Io {
+            path: target.clone(),
+            source,
+        })?;
+        self.persisted = true;
+
+        Ok(target)
+    }
+
+    /// Publishes under `target` only if nothing is there yet. A hard link
+    /// fails atomically when the name exists, unlike a rename.
+    fn link(self, target: PathBuf) -> Result<PathBuf, StatementError> {
+        fs::hard_link(&self.path, &target).map_err(|source| StatementError::Io {
+            path: target.clone(),
+            source,
+        })?;
+
+        Ok(target)
+    }
+
+    fn link_versioned(self, target: PathBuf) -> Result<PathBuf, StatementError> {
+        let stem = target
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let extension = target
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+
+        let mut candidate = target;
+        let mut version = 0;
+        loop {
+            match fs::hard_link(&self.path, &candidate) {
+                Ok(()) => return Ok(candidate),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    version += 1;
+                    candidate.set_file_name(format!("{}-{}{}", stem, version, extension));
+                }
+                Err(source) => {
+                    return Err(StatementError::Io {
+                        path: candidate,
+                        source,
+                    })
+                }
+            }
+        }
+    }
+
+    fn io_error(&self, source: io::Error) -> StatementError {
+        StatementError::Io {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        process,
+    };
+
+    use super::{check_component, FileNameParts, OutputConfig};
+    use crate::pdf::error::StatementError;
+
+    fn parts<'a>(member_no: &'a str, pdf_name: &'a str) -> FileNameParts<'a> {
+        FileNameParts {
+            member_no,
+            account_no: "001-00020-001",
+            security_code: "001",
+            period_end: "2023-12-31".to_string(),
+            pdf_name,
+        }
+    }
+
+    fn template(template: &str) -> OutputConfig {
+        OutputConfig {
+            template: template.to_string(),
+            ..OutputConfig::default()
+        }
+    }
+
+    /// A fresh directory for one test, removed when it is dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "full_statement-output-{}-{}",
+                process::id(),
+                name
+            ));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn is_unsafe<T: std::fmt::Debug>(result: Result<T, StatementError>) -> bool {
+        matches!(result, Err(StatementError::UnsafePath(_)))
+    }
+
+    #[test]
+    fn default_template_stays_below_root() {
+        let path = OutputConfig::default()
+            .path_for(Path::new("storage"), &parts("00020", "statement"))
+            .unwrap();
+
+        assert_eq!(path, Path::new("storage/00020/statement.pdf"));
+    }
+
+    #[test]
+    fn fills_every_placeholder() {
+        let path = template("{security_code}/{account_no}/{member_no}-{period_end}-{pdf_name}.pdf")
+            .path_for(Path::new("root"), &parts("00020", "statement"))
+            .unwrap();
+
+        assert_eq!(
+            path,
+            Path::new("root/001/001-00020-001/00020-2023-12-31-statement.pdf")
+        );
+    }
+
+    #[test]
+    fn rejects_unsafe_values() {
+        let config = OutputConfig::default();
+        for value in [
+            "..",
+            ".",
+            "",
+            "../../etc/x",
+            "/etc/passwd",
+            "a/b",
+            "a\\b",
+            "a\nb",
+            "a\0b",
+        ] {
+            assert!(
+                is_unsafe(config.path_for(Path::new("storage"), &parts(value, "statement"))),
+                "member_no {:?}",
+                value
+            );
+            assert!(
+                is_unsafe(config.path_for(Path::new("storage"), &parts("00020", value))),
+                "pdf_name {:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn allows_plain_names() {
+        for value in ["00020", "a.b", "...", "Jane Doe", "Müller"] {
+            assert!(check_component("member_no", value).is_ok(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_templates_leaving_root() {
+        for bad in [
+            "../{pdf_name}.pdf",
+            "{member_no}/../../{pdf_name}.pdf",
+            "/etc/cron.d/{pdf_name}",
+            "",
+        ] {
+            assert!(
+                is_unsafe(template(bad).path_for(Path::new("storage"), &parts("00020", "s"))),
+                "template {:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_and_unclosed_placeholders() {
+        for bad in ["{root}/{pdf_name}.pdf", "{member_no/{pdf_name}.pdf"] {
+            let result = template(bad).path_for(Path::new("storage"), &parts("00020", "s"));
+            assert!(
+                matches!(result, Err(StatementError::InvalidConfig(_))),
+                "template {:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn payload_cannot_choose_root() {
+        let json = r#"{ "root": "/etc/cron.d", "template": "{pdf_name}.pdf" }"#;
+
+        assert!(serde_json::from_str::<OutputConfig>(json).is_err());
+    }
+
+    #[test]
+    fn writes_below_root() {
+        let dir = TempDir::new("writes");
+        let root = dir.0.join("storage");
+        let config = OutputConfig::default();
+        let path = config.path_for(&root, &parts("00020", "s")).unwrap();
+
+        let written = config.write(&root, path.clone(), b"%PDF").unwrap();
+
+        assert_eq!(written, path);
+        assert_eq!(fs::read(&path).unwrap(), b"%PDF");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_under_root_cannot_escape() {
+        let dir = TempDir::new("symlink");
+        let root = dir.0.join("storage");
+        let outside = dir.0.join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("00020")).unwrap();
+
+        let config = template("{member_no}/nested/{pdf_name}.pdf");
+        let path = config.path_for(&root, &parts("00020", "s")).unwrap();
+
+        assert!(is_unsafe(config.write(&root, path, b"%PDF")));
+        // nothing was created through the link
+        assert_eq!(fs::read_dir(&outside).unwrap().count(), 0);
+    }
+}
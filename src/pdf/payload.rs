@@ -0,0 +1,343 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{
+    de::{Error as _, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use serde_json::Value;
+use tslink::tslink;
+
+use super::{
+    branding::Branding, columns::ColumnConfig, fonts::FontConfig, output::OutputConfig,
+    rounding::Rounding,
+};
+
+/// Current payload schema. Payloads without a `version` are read as the
+/// legacy flat format.
+pub const PAYLOAD_VERSION: u64 = 2;
+
+/// A statement request: who it is for, which account and fund it covers and
+/// the rows to print. Build one with [`Payload::from_json`] or
+/// [`Payload::from_value`] so legacy payloads are converted as well.
+#[tslink]
+#[derive(Debug, Clone, Deserialize)]
+pub struct Payload {
+    pub version: u64,
+    pub pdf_name: String,
+    pub customer: Customer,
+    pub account: Account,
+    pub fund: Fund,
+    #[serde(deserialize_with = "rows")]
+    pub transactions: Vec<Transaction>,
+    pub output: Option<OutputConfig>,
+    pub rounding: Option<Rounding>,
+    // replaces the fund's default table columns
+    pub columns: Option<Vec<ColumnConfig>>,
+    // embedded instead of builtin Helvetica
+    pub fonts: Option<FontConfig>,
+    // the issuing company's logo, address and colors
+    pub branding: Option<Branding>,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Customer {
+    pub member_no: String,
+    pub name: String,
+    pub email: String,
+    pub post_address: String,
+    pub phone: String,
+    pub town: String,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Account {
+    pub account_no: String,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Fund {
+    pub name: String,
+    pub security_code: String,
+    pub currency: String,
+    // printed in the footer of every page
+    pub disclaimer: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Transaction {
+    pub trans_id: i64,
+    // optional on each row; checked against the customer and account
+    pub member_no: Option<String>,
+    pub account_no: Option<String>,
+    pub trans_date: DateTime<Utc>,
+    pub trans_type: TransactionType,
+    pub amount: Decimal,
+    pub taxamt: Decimal,
+    pub running_balance: Decimal,
+    pub running_shares: Decimal,
+    pub shares: Option<Decimal>,
+    pub price: Option<Decimal>,
+    pub netamount: Decimal,
+    pub mop: String,
+    pub p_amount: Decimal,
+    pub w_amount: Decimal,
+    pub i_amount: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionType {
+    #[serde(alias = "DEPOSIT", alias = "SUBSCRIPTION", alias = "BUY")]
+    Purchase,
+    #[serde(alias = "REDEMPTION", alias = "SALE", alias = "SELL")]
+    Withdrawal,
+    Interest,
+    #[serde(alias = "SWITCH-IN", alias = "SWITCHIN")]
+    SwitchIn,
+    #[serde(alias = "SWITCH-OUT", alias = "SWITCHOUT")]
+    SwitchOut,
+    #[serde(alias = "TRANSFER-IN", alias = "TRANSFERIN")]
+    TransferIn,
+    #[serde(alias = "TRANSFER-OUT", alias = "TRANSFEROUT")]
+    TransferOut,
+    #[serde(alias = "DISTRIBUTION")]
+    Dividend,
+    #[serde(alias = "DIVIDEND_REINVESTMENT")]
+    Reinvestment,
+    #[serde(alias = "CHARGE")]
+    Fee,
+    Reversal,
+    Adjustment,
+}
+
+/// The type as printed on a statement.
+impl fmt::Display for TransactionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            TransactionType::Purchase => "Purchase",
+            TransactionType::Withdrawal => "Withdrawal",
+            TransactionType::Interest => "Interest",
+            TransactionType::SwitchIn => "Switch In",
+            TransactionType::SwitchOut => "Switch Out",
+            TransactionType::TransferIn => "Transfer In",
+            TransactionType::TransferOut => "Transfer Out",
+            TransactionType::Dividend => "Dividend",
+            TransactionType::Reinvestment => "Reinvestment",
+            TransactionType::Fee => "Fee",
+            TransactionType::Reversal => "Reversal",
+            TransactionType::Adjustment => "Adjustment",
+        })
+    }
+}
+
+/// Which side of the statement a row lands on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    /// Money and units into the account.
+    Deposit,
+    /// Money and units out of the account.
+    Withdrawal,
+    /// Earnings paid on the balance; no units change hands.
+    Income,
+}
+
+impl Transaction {
+    /// Reversals and adjustments can go either way, so their direction is
+    /// taken from the sign of `amount`.
+    pub fn movement(&self) -> Movement {
+        match self.trans_type {
+            TransactionType::Purchase
+            | TransactionType::SwitchIn
+            | TransactionType::TransferIn
+            | TransactionType::Reinvestment => Movement::Deposit,
+            TransactionType::Withdrawal
+            | TransactionType::SwitchOut
+            | TransactionType::TransferOut
+            | TransactionType::Fee => Movement::Withdrawal,
+            TransactionType::Interest | TransactionType::Dividend => Movement::Income,
+            TransactionType::Reversal | TransactionType::Adjustment => {
+                if self.amount.is_sign_negative() {
+                    Movement::Withdrawal
+                } else {
+                    Movement::Deposit
+                }
+            }
+        }
+    }
+}
+
+impl Payload {
+    pub fn from_json(json: &str) -> Result<Payload, serde_json::Error> {
+        Payload::from_value(serde_json::from_str(json)?)
+    }
+
+    pub fn from_value(value: Value) -> Result<Payload, serde_json::Error> {
+        match value.get("version") {
+            None => Ok(LegacyPayload::deserialize(value)?.into()),
+            Some(version) if version.as_u64() == Some(PAYLOAD_VERSION) => {
+                Payload::deserialize(value)
+            }
+            Some(version) => Err(serde_json::Error::custom(format!(
+                "unsupported payload version {}, expected {}",
+                version, PAYLOAD_VERSION
+            ))),
+        }
+    }
+
+    pub fn is_legacy(value: &Value) -> bool {
+        value.get("version").is_none()
+    }
+}
+
+/// The original flat format, where every row repeats the customer, account
+/// and fund details.
+#[derive(Debug, Deserialize)]
+pub struct LegacyPayload {
+    pub pdf_name: String,
+    #[serde(deserialize_with = "rows")]
+    pub transactions: Vec<LegacyTransaction>,
+    pub output: Option<OutputConfig>,
+    pub rounding: Option<Rounding>,
+    pub columns: Option<Vec<ColumnConfig>>,
+    pub fonts: Option<FontConfig>,
+    pub branding: Option<Branding>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LegacyTransaction {
+    pub member_no: String,
+    town: String,
+    e_mail: String,
+    allnames: String,
+    post_address: String,
+    gsm_no: String,
+    descript: String,
+    security_code: String,
+    account_no: String,
+    currency: String,
+    statement: String,
+    #[serde(flatten)]
+    row: Transaction,
+}
+
+/// Takes the customer, account and fund details from the last row, which is
+/// what the flat format has always been rendered from.
+impl From<LegacyPayload> for Payload {
+    fn from(legacy: LegacyPayload) -> Self {
+        let mut payload = Payload {
+            version: PAYLOAD_VERSION,
+            pdf_name: legacy.pdf_name,
+            customer: Customer::default(),
+            account: Account::default(),
+            fund: Fund::default(),
+            transactions: Vec::with_capacity(legacy.transactions.len()),
+            output: legacy.output,
+            rounding: legacy.rounding,
+            columns: legacy.columns,
+            fonts: legacy.fonts,
+            branding: legacy.branding,
+        };
+
+        let mut rows = legacy.transactions.into_iter().peekable();
+        while let Some(trans) = rows.next() {
+            if rows.peek().is_none() {
+                payload.customer = Customer {
+                    member_no: trans.member_no.clone(),
+                    name: trans.allnames,
+                    email: trans.e_mail,
+                    post_address: trans.post_address,
+                    phone: trans.gsm_no,
+                    town: trans.town,
+                };
+                payload.account = Account {
+                    account_no: trans.account_no.clone(),
+                };
+                payload.fund = Fund {
+                    name: trans.descript,
+                    security_code: trans.security_code,
+                    currency: trans.currency,
+                    disclaimer: trans.statement,
+                };
+            }
+            let mut row = trans.row;
+            row.member_no = Some(trans.member_no);
+            row.account_no = Some(trans.account_no);
+            payload.transactions.push(row);
+        }
+
+        payload
+    }
+}
+
+/// Deserializes transaction rows, prefixing any error with the row it came
+/// from so an unknown `trans_type` deep in a long payload is easy to find.
+fn rows<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct Rows<T>(std::marker::PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for Rows<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of transactions")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+            let mut rows = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            loop {
+                match seq.next_element() {
+                    Ok(Some(row)) => rows.push(row),
+                    Ok(None) => return Ok(rows),
+                    Err(err) => {
+                        return Err(A::Error::custom(format!(
+                            "transaction row {}: {}",
+                            rows.len(),
+                            err
+                        )))
+                    }
+                }
+            }
+        }
+    }
+
+    deserializer.deserialize_seq(Rows(std::marker::PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::Payload;
+
+    #[test]
+    fn json_numbers_are_read_exactly() {
+        let json = r#"{
+            "version": 2,
+            "pdf_name": "statement",
+            "customer": {
+                "member_no": "00020", "name": "", "email": "",
+                "post_address": "", "phone": "", "town": ""
+            },
+            "account": { "account_no": "001-00020-001" },
+            "fund": { "name": "", "security_code": "", "currency": "", "disclaimer": "" },
+            "transactions": [{
+                "trans_id": 1, "trans_date": "2023-01-01T00:00:00Z", "trans_type": "PURCHASE",
+                "amount": 1234567890123.4567891, "taxamt": 0, "running_balance": 0.1,
+                "running_shares": 0, "netamount": 0, "mop": "", "p_amount": 0,
+                "w_amount": 0, "i_amount": 0
+            }]
+        }"#;
+
+        let row = &Payload::from_json(json).unwrap().transactions[0];
+        assert_eq!(row.amount, dec!(1234567890123.4567891));
+        assert_eq!(row.running_balance, dec!(0.1));
+    }
+}
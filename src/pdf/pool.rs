@@ -0,0 +1,123 @@
+use std::{
+    collections::VecDeque,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{Condvar, Mutex, OnceLock},
+    thread,
+};
+
+use futures::channel::oneshot;
+
+use super::error::StatementError;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Worker threads for rendering so callers running on an async executor
+/// (the Node binding) never do PDF work on their own threads. Workers are
+/// started on demand up to `limit` and exit when the limit is lowered.
+struct Pool {
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+struct PoolState {
+    queue: VecDeque<Job>,
+    limit: usize,
+    workers: usize,
+    idle: usize,
+}
+
+fn pool() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+
+    POOL.get_or_init(|| Pool {
+        state: Mutex::new(PoolState {
+            queue: VecDeque::new(),
+            limit: default_concurrency(),
+            workers: 0,
+            idle: 0,
+        }),
+        available: Condvar::new(),
+    })
+}
+
+pub fn default_concurrency() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Caps how many statements render at the same time; queued work waits for
+/// a free worker. A limit of zero is treated as one.
+pub fn set_concurrency(limit: usize) {
+    let pool = pool();
+    let mut state = pool.state.lock().unwrap();
+    state.limit = limit.max(1);
+    drop(state);
+    pool.available.notify_all();
+}
+
+pub fn concurrency() -> usize {
+    pool().state.lock().unwrap().limit
+}
+
+/// Runs `job` on the render pool and resolves once it has finished. A panic
+/// inside the job is reported as a render error instead of killing the worker.
+pub async fn spawn<T, F>(job: F) -> Result<T, StatementError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, StatementError> + Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+
+    submit(Box::new(move || {
+        let result = catch_unwind(AssertUnwindSafe(job))
+            .unwrap_or_else(|_| Err(StatementError::Render("render worker panicked".to_string())));
+        let _ = sender.send(result);
+    }));
+
+    receiver
+        .await
+        .unwrap_or_else(|_| Err(StatementError::Render("render worker stopped".to_string())))
+}
+
+fn submit(job: Job) {
+    let pool = pool();
+    let mut state = pool.state.lock().unwrap();
+    state.queue.push_back(job);
+
+    // idle workers may already be claimed by jobs queued before this one
+    if state.queue.len() > state.idle && state.workers < state.limit {
+        state.workers += 1;
+        thread::Builder::new()
+            .name("statement-render".to_string())
+            .spawn(work)
+            .expect("failed to spawn render worker");
+    }
+    drop(state);
+    pool.available.notify_one();
+}
+
+fn work() {
+    let pool = pool();
+    let mut state = pool.state.lock().unwrap();
+
+    loop {
+        if state.workers > state.limit {
+            state.workers -= 1;
+            return;
+        }
+
+        match state.queue.pop_front() {
+            Some(job) => {
+                drop(state);
+                job();
+                state = pool.state.lock().unwrap();
+            }
+            None => {
+                state.idle += 1;
+                state = pool.available.wait(state).unwrap();
+                state.idle -= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use tslink::tslink;
+
+/// Decimal places figures are printed to, unless they are small enough to
+/// need four.
+pub(crate) const PLACES: u32 = 2;
+
+/// The smallest difference a printed figure shows.
+pub fn step() -> Decimal {
+    Decimal::new(1, PLACES)
+}
+
+/// How each kind of figure is rounded when printed. Sums are always taken
+/// over the exact values and only rounded for display.
+#[tslink]
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Rounding {
+    // transaction amounts, totals and market value
+    pub amount: RoundingMode,
+    pub tax: RoundingMode,
+    pub balance: RoundingMode,
+    pub units: RoundingMode,
+    // unit prices and NAV
+    pub price: RoundingMode,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    // midpoints away from zero: 0.125 -> 0.13
+    #[default]
+    HalfUp,
+    // midpoints to the even digit: 0.125 -> 0.12
+    #[serde(alias = "half_even")]
+    Bankers,
+}
+
+impl RoundingMode {
+    fn strategy(self) -> RoundingStrategy {
+        match self {
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::Bankers => RoundingStrategy::MidpointNearestEven,
+        }
+    }
+
+    /// Two decimal places with thousands separators. Values below one whose
+    /// first two decimals are zero get four places so they don't print as
+    /// `0.00`.
+    pub fn format(self, value: Decimal) -> String {
+        let fract = value.fract().abs();
+        if value.abs() < Decimal::ONE && !fract.is_zero() && fract < dec!(0.01) {
+            return format!("{:.4}", value.round_dp_with_strategy(4, self.strategy()));
+        }
+
+        let rounded = value.round_dp_with_strategy(PLACES, self.strategy());
+        let text = format!("{:.*}", PLACES as usize, rounded.abs());
+        let (whole, decimals) = text.split_once('.').unwrap_or((&text, "00"));
+
+        let mut grouped = String::with_capacity(text.len() + whole.len() / 3 + 1);
+        if rounded.is_sign_negative() && !rounded.is_zero() {
+            grouped.push('-');
+        }
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        grouped.push('.');
+        grouped.push_str(decimals);
+
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::RoundingMode::{Bankers, HalfUp};
+
+    #[test]
+    fn midpoints() {
+        assert_eq!(HalfUp.format(dec!(0.125)), "0.13");
+        assert_eq!(Bankers.format(dec!(0.125)), "0.12");
+        assert_eq!(HalfUp.format(dec!(0.135)), "0.14");
+        assert_eq!(Bankers.format(dec!(0.135)), "0.14");
+        assert_eq!(HalfUp.format(dec!(-2.675)), "-2.68");
+        assert_eq!(Bankers.format(dec!(-2.665)), "-2.66");
+    }
+
+    #[test]
+    fn groups_thousands() {
+        assert_eq!(HalfUp.format(dec!(0)), "0.00");
+        assert_eq!(HalfUp.format(dec!(999.999)), "1,000.00");
+        assert_eq!(HalfUp.format(dec!(1234567.891)), "1,234,567.89");
+        assert_eq!(HalfUp.format(dec!(100000)), "100,000.00");
+    }
+
+    #[test]
+    fn negatives() {
+        assert_eq!(HalfUp.format(dec!(-1234.5)), "-1,234.50");
+        assert_eq!(HalfUp.format(dec!(-1234.005)), "-1,234.01");
+        assert_eq!(Bankers.format(dec!(-1234.005)), "-1,234.00");
+        assert_eq!(HalfUp.format(dec!(-1000000)), "-1,000,000.00");
+    }
+
+    #[test]
+    fn small_values_get_four_places() {
+        assert_eq!(HalfUp.format(dec!(0.0012)), "0.0012");
+        assert_eq!(HalfUp.format(dec!(0.00005)), "0.0001");
+        assert_eq!(HalfUp.format(dec!(-0.004)), "-0.0040");
+        assert_eq!(HalfUp.format(dec!(0.05)), "0.05");
+        // only below one; larger values keep two places and separators
+        assert_eq!(HalfUp.format(dec!(1.004)), "1.00");
+        assert_eq!(HalfUp.format(dec!(-5000.001)), "-5,000.00");
+    }
+}
@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tslink::tslink;
+
+use super::{columns::Field, validate::ValidationIssue};
+
+/// What a statement would contain, for reconciling against the ledger
+/// before printing. Amounts are exact decimal strings, not the rounded
+/// figures printed on the PDF. Totals come from the same summation row the
+/// statement prints; `mmf_totals` and `bf_totals` total the fund's default
+/// columns whatever the payload's `columns`.
+#[tslink]
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementSummary {
+    pub member_no: String,
+    pub account_no: String,
+    pub security_code: String,
+    pub pages: usize,
+    pub rows_per_page: Vec<usize>,
+    // first and last transaction dates
+    pub period_start: String,
+    pub closing_date: String,
+    // closing balance for money market funds, units x NAV for balanced funds
+    pub market_value: String,
+    // the summation row as printed, one entry per table column
+    pub summation: Vec<ColumnTotal>,
+    pub mmf_totals: Option<MmfTotals>,
+    pub bf_totals: Option<BfTotals>,
+    pub warnings: Vec<ValidationIssue>,
+}
+
+/// What the summation row prints under one column; `total` is null where
+/// the column has no total.
+#[tslink]
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnTotal {
+    pub field: Field,
+    pub total: Option<String>,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Serialize)]
+pub struct MmfTotals {
+    pub running_balance: String,
+    pub tax: String,
+    pub deposits: String,
+    pub withdrawals: String,
+    pub interest: String,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Serialize)]
+pub struct BfTotals {
+    pub purchase_units: String,
+    pub purchase_costs: String,
+    pub sale_units: String,
+    pub sale_costs: String,
+    pub balance_units: String,
+    pub latest_nav: String,
+}
+
+/// Written next to each PDF so archive and dispatch systems can index
+/// statements without parsing them.
+#[derive(Debug, Serialize)]
+pub struct StatementManifest<'a> {
+    #[serde(flatten)]
+    pub summary: &'a StatementSummary,
+    pub file_name: String,
+    pub file_size: u64,
+    pub sha256: String,
+    pub generated_at: String,
+}
+
+impl<'a> StatementManifest<'a> {
+    pub fn new(summary: &'a StatementSummary, path: &Path, bytes: &[u8]) -> Self {
+        StatementManifest {
+            summary,
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            file_size: bytes.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(bytes)),
+            generated_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use printpdf::{Color, IndirectFontRef, Line, Mm, PdfLayerReference, Point, Rgb};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use tslink::tslink;
+
+use super::{
+    branding::Palette,
+    fonts::Fonts,
+    layout::PAGE_HEIGHT,
+    metrics::{Face, Metrics},
+    payload::Transaction,
+    rounding::{self, Rounding},
+};
+
+/// Left edge of every table, in mm.
+const TABLE_X: f32 = 10.0;
+/// Widest a table can be between the page margins, in mm.
+pub(crate) const TABLE_WIDTH: f32 = 190.0;
+/// Space between a cell's edges and its text.
+const CELL_PADDING: f32 = 2.0;
+/// Distance from the top of a row to the baseline of its first line.
+const BASELINE: f32 = 5.0;
+
+/// A cell's text as printed: its lines and the size they are set at.
+pub struct CellText {
+    pub lines: Vec<String>,
+    pub size: f32,
+}
+
+/// How a run of text is set.
+struct Pen<'a> {
+    font: &'a IndirectFontRef,
+    face: Face,
+    size: f32,
+    color: Rgb,
+}
+
+/// A statement table: the columns to print and how tall its rows are. Each
+/// fund type is a different set of columns drawn by the same code.
+pub struct Table {
+    pub columns: Vec<Column>,
+    // where the table starts on the first page, under the customer details,
+    // and on the following pages, in mm from the bottom
+    pub top_first: f32,
+    pub top: f32,
+    pub row_height: f32,
+    // added to a row for every extra line a cell wraps to
+    pub line_height: f32,
+    pub font_size: f32,
+    pub header_size: f32,
+    pub summary_rows: usize,
+    // what cell text is measured with; must match the fonts it is drawn in
+    pub metrics: Metrics,
+    pub palette: Palette,
+}
+
+pub struct Column {
+    pub title: String,
+    // columns next to each other with the same group share a title above
+    // their own
+    pub group: Option<String>,
+    pub width: f32,
+    pub align: Align,
+    pub color: Rgb,
+    // overrides the table's font size for this column's rows
+    pub size: Option<f32>,
+    pub overflow: Overflow,
+    pub format: NumberFormat,
+    pub value: fn(&Transaction) -> Cell,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+    // numbers line up on their decimal point, the column's widest fraction
+    // ending at the right padding
+    Decimal,
+}
+
+/// What happens to text wider than its cell. Numbers are never cut.
+#[tslink]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    // more lines, making the row taller
+    #[default]
+    Wrap,
+    // one line ending in "..."
+    Truncate,
+    // one line at a smaller size, truncated below 5pt
+    Shrink,
+}
+
+/// What a column shows for a row, before formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    Number(Decimal),
+}
+
+/// How a column's numbers are printed; each kind follows its own rounding
+/// mode from the payload.
+#[tslink]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberFormat {
+    // exact value, no rounding or separators
+    Text,
+    Amount,
+    Tax,
+    Balance,
+    Units,
+    Price,
+}
+
+impl NumberFormat {
+    /// The smallest difference the format shows; text shows the exact value.
+    pub fn step(self) -> Decimal {
+        match self {
+            NumberFormat::Text => Decimal::ZERO,
+            _ => rounding::step(),
+        }
+    }
+
+    pub fn apply(self, cell: &Cell, rounding: &Rounding) -> String {
+        let value = match cell {
+            Cell::Empty => return String::new(),
+            Cell::Text(text) => return text.clone(),
+            Cell::Number(value) => *value,
+        };
+
+        match self {
+            NumberFormat::Text => value.to_string(),
+            NumberFormat::Amount => rounding.amount.format(value),
+            NumberFormat::Tax => rounding.tax.format(value),
+            NumberFormat::Balance => rounding.balance.format(value),
+            NumberFormat::Units => rounding.units.format(value),
+            NumberFormat::Price => rounding.price.format(value),
+        }
+    }
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Table {
+            columns,
+            top_first: PAGE_HEIGHT - 66.0,
+            top: PAGE_HEIGHT - 22.0,
+            row_height: 8.0,
+            line_height: 3.5,
+            font_size: 8.0,
+            header_size: 7.5,
+            summary_rows: 1,
+            metrics: Metrics::default(),
+            palette: Palette::default(),
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.columns.iter().map(|c| c.width).sum()
+    }
+
+    fn has_groups(&self) -> bool {
+        self.columns.iter().any(|c| c.group.is_some())
+    }
+
+    /// The column titles, plus a row of group titles when there are any.
+    pub fn header_height(&self) -> f32 {
+        if self.has_groups() {
+            self.row_height * 2.0
+        } else {
+            self.row_height
+        }
+    }
+
+    pub fn summary_height(&self) -> f32 {
+        self.summary_rows as f32 * self.row_height
+    }
+
+    /// Each cell of a row as printed, with text fitted to its column.
+    pub fn row_cells(&self, t: &Transaction, rounding: &Rounding) -> Vec<CellText> {
+        self.columns
+            .iter()
+            .map(|column| {
+                let cell = (column.value)(t);
+                let text = column.format.apply(&cell, rounding);
+                let size = self.cell_size(column);
+                let max_width = column.width - CELL_PADDING * 2.0;
+                if !matches!(cell, Cell::Text(_)) {
+                    return CellText {
+                        lines: vec![text],
+                        size,
+                    };
+                }
+
+                match column.overflow {
+                    Overflow::Wrap => CellText {
+                        lines: self.metrics.wrap(&text, Face::Regular, size, max_width),
+                        size,
+                    },
+                    Overflow::Truncate => CellText {
+                        lines: vec![self.metrics.truncate(&text, Face::Regular, size, max_width)],
+                        size,
+                    },
+                    Overflow::Shrink => {
+                        let (line, size) =
+                            self.metrics.fit_line(&text, Face::Regular, size, max_width);
+                        CellText {
+                            lines: vec![line],
+                            size,
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn row_height(&self, t: &Transaction, rounding: &Rounding) -> f32 {
+        let cells = self.row_cells(t, rounding);
+        let extra_lines = cells.iter().map(|c| c.lines.len()).max().unwrap_or(1) - 1;
+
+        self.row_height + extra_lines as f32 * self.line_height
+    }
+
+    fn cell_size(&self, column: &Column) -> f32 {
+        column.size.unwrap_or(self.font_size)
+    }
+
+    /// Draws the header and `rows` from `top` down, then `summary` when this
+    /// is the last page.
+    pub fn draw(
+        &self,
+        layer: &PdfLayerReference,
+        top: f32,
+        fonts: &Fonts,
+        rows: &[Transaction],
+        summary: Option<&[Vec<Cell>]>,
+        rounding: &Rounding,
+    ) {
+        let cells: Vec<Vec<CellText>> = rows.iter().map(|t| self.row_cells(t, rounding)).collect();
+        // summation cells may run into their empty neighbours, so they aren't fitted
+        let summary: Vec<Vec<CellText>> = summary
+            .unwrap_or_default()
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, cell)| CellText {
+                        lines: vec![column.format.apply(cell, rounding)],
+                        size: self.font_size,
+                    })
+                    .collect()
+            })
+            .collect();
+        let fractions = self.fraction_widths(&cells, &summary);
+
+        let mut y = top;
+        self.rule(layer, y, self.palette.primary.clone());
+
+        if self.has_groups() {
+            self.draw_groups(layer, y, fonts);
+            y -= self.row_height;
+            self.rule(layer, y, self.palette.primary.clone());
+        }
+
+        for (x, column) in self.cells() {
+            let (title, size) = self.metrics.fit_line(
+                &column.title,
+                Face::Bold,
+                self.header_size,
+                column.width - CELL_PADDING * 2.0,
+            );
+            let pen = Pen {
+                font: fonts.get(Face::Bold),
+                face: Face::Bold,
+                size,
+                color: column.color.clone(),
+            };
+            let align = match column.align {
+                Align::Decimal => Align::Right,
+                align => align,
+            };
+            self.text(layer, x, y, column, align, &[title], &pen, 0.0);
+        }
+        y -= self.row_height;
+        self.rule(layer, y, gray());
+
+        for (t, cells) in rows.iter().zip(&cells) {
+            for (((x, column), cell), fraction) in self.cells().zip(cells).zip(&fractions) {
+                let pen = Pen {
+                    font: fonts.get(Face::Regular),
+                    face: Face::Regular,
+                    size: cell.size,
+                    color: column.color.clone(),
+                };
+                self.text(
+                    layer,
+                    x,
+                    y,
+                    column,
+                    column.align,
+                    &cell.lines,
+                    &pen,
+                    *fraction,
+                );
+            }
+            y -= self.row_height(t, rounding);
+            self.rule(layer, y, gray());
+        }
+
+        if summary.is_empty() {
+            return;
+        }
+
+        self.rule(layer, y, self.palette.primary.clone());
+        for row in &summary {
+            for (((x, column), cell), fraction) in self.cells().zip(row).zip(&fractions) {
+                let pen = Pen {
+                    font: fonts.get(Face::Regular),
+                    face: Face::Regular,
+                    size: cell.size,
+                    color: self.palette.primary.clone(),
+                };
+                self.text(
+                    layer,
+                    x,
+                    y,
+                    column,
+                    column.align,
+                    &cell.lines,
+                    &pen,
+                    *fraction,
+                );
+            }
+            y -= self.row_height;
+            self.rule(layer, y, self.palette.primary.clone());
+        }
+    }
+
+    /// Width of the widest decimal point and fraction printed in each column,
+    /// which decimal-aligned columns keep clear on the right.
+    fn fraction_widths(&self, rows: &[Vec<CellText>], summary: &[Vec<CellText>]) -> Vec<f32> {
+        (0..self.columns.len())
+            .map(|i| {
+                rows.iter()
+                    .chain(summary)
+                    .filter_map(|row| row.get(i))
+                    .flat_map(|cell| {
+                        cell.lines.iter().map(|line| {
+                            self.metrics
+                                .text_width(fraction(line), Face::Regular, cell.size)
+                        })
+                    })
+                    .fold(0.0, f32::max)
+            })
+            .collect()
+    }
+
+    /// Left edge of each column with the column itself.
+    fn cells(&self) -> impl Iterator<Item = (f32, &Column)> {
+        self.columns.iter().scan(TABLE_X, |x, column| {
+            let left = *x;
+            *x += column.width;
+            Some((left, column))
+        })
+    }
+
+    fn draw_groups(&self, layer: &PdfLayerReference, top: f32, fonts: &Fonts) {
+        let mut spans: Vec<(f32, f32, &Column)> = vec![];
+        for (x, column) in self.cells() {
+            match spans.last_mut() {
+                Some((_, width, first)) if first.group == column.group => *width += column.width,
+                _ => spans.push((x, column.width, column)),
+            }
+        }
+
+        for (x, width, first) in spans {
+            let Some(title) = &first.group else {
+                continue;
+            };
+            let (title, size) = self.metrics.fit_line(
+                title,
+                Face::Bold,
+                self.header_size,
+                width - CELL_PADDING * 2.0,
+            );
+            let text_width = self.metrics.text_width(&title, Face::Bold, size);
+            layer.set_fill_color(Color::Rgb(first.color.clone()));
+            layer.use_text(
+                title.as_str(),
+                size,
+                Mm(x + (width - text_width) / 2.0),
+                Mm(top - BASELINE),
+                fonts.get(Face::Bold),
+            );
+        }
+    }
+
+    /// Sets `lines` in the cell whose left edge is `x`. `fraction` is the
+    /// space a decimal-aligned column keeps right of its decimal points.
+    #[allow(clippy::too_many_arguments)]
+    fn text(
+        &self,
+        layer: &PdfLayerReference,
+        x: f32,
+        top: f32,
+        column: &Column,
+        align: Align,
+        lines: &[String],
+        pen: &Pen,
+        fraction_width: f32,
+    ) {
+        layer.set_fill_color(Color::Rgb(pen.color.clone()));
+        for (i, line) in lines.iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let width = |text: &str| self.metrics.text_width(text, pen.face, pen.size);
+            let right = x + column.width - CELL_PADDING;
+            let text_x = match align {
+                Align::Left => x + CELL_PADDING,
+                Align::Center => x + (column.width - width(line)) / 2.0,
+                Align::Right => right - width(line),
+                Align::Decimal => {
+                    let whole = &line[..line.len() - fraction(line).len()];
+                    right - fraction_width - width(whole)
+                }
+            };
+            let text_y = top - BASELINE - i as f32 * self.line_height;
+            layer.use_text(line.as_str(), pen.size, Mm(text_x), Mm(text_y), pen.font);
+        }
+    }
+
+    fn rule(&self, layer: &PdfLayerReference, y: f32, color: Rgb) {
+        let line = Line {
+            points: vec![
+                (Point::new(Mm(TABLE_X), Mm(y)), false),
+                (Point::new(Mm(TABLE_X + self.width()), Mm(y)), false),
+            ],
+            is_closed: false,
+        };
+        layer.set_outline_thickness(0.7);
+        layer.set_outline_color(Color::Rgb(color));
+        layer.add_line(line);
+    }
+}
+
+/// The decimal point and digits after it, or nothing for whole numbers and
+/// text.
+fn fraction(text: &str) -> &str {
+    match text.rfind('.') {
+        Some(i) if text[i + 1..].chars().all(|c| c.is_ascii_digit()) => &text[i..],
+        _ => "",
+    }
+}
+
+pub fn gray() -> Rgb {
+    Rgb::new(230.0 / 256.0, 230.0 / 256.0, 230.0 / 256.0, None)
+}
+
+pub fn dark() -> Rgb {
+    Rgb::new(80.0 / 256.0, 80.0 / 256.0, 80.0 / 256.0, None)
+}
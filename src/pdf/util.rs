@@ -1,85 +1,109 @@
 mod bf;
 mod mf;
 
-use ::image::{open, DynamicImage};
 use chrono::{DateTime, Utc};
-use numfmt::Formatter;
 use printpdf::{
-    BuiltinFont, Image, ImageTransform, ImageXObject, IndirectFontRef, Mm, PdfConformance,
-    PdfDocument, PdfLayerReference, Px,
+    ImageTransform, IndirectFontRef, Mm, PdfConformance, PdfDocument, PdfLayerReference,
+};
+use rust_decimal::Decimal;
+use std::{ops::Range, path::PathBuf};
+
+use bf::{bf_columns, closing_row};
+use mf::mmf_columns;
+
+use super::{
+    branding::{Branding, Palette},
+    columns::{self, check_columns, totals_row, ColumnConfig, Field},
+    error::StatementError,
+    fonts::{FontConfig, FontFiles, Fonts},
+    layout::{self, Footer, FOOTER_SIZE, PAGE_HEIGHT},
+    logo::{load_logo, share_images, Logo},
+    metrics::{Face, Metrics, MM_PER_PT},
+    output::{self, FileNameParts},
+    payload::{Payload, Transaction},
+    rounding::Rounding,
+    summary::{BfTotals, ColumnTotal, MmfTotals, StatementManifest, StatementSummary},
+    table::{Cell, Table},
+    validate::{validate, ValidationIssue},
 };
-use serde::{Deserialize, Serialize};
-use std::{fs::File, io::BufWriter, path::Path};
-use textwrap::wrap;
-
-use bf::gen_table;
-use mf::gen_table_mmf;
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Transaction {
-    member_no: String,
-    town: String,
-    e_mail: String,
-    allnames: String,
-    post_address: String,
-    gsm_no: String,
-    descript: String,
-    security_code: String,
-    trans_id: i64,
-    trans_date: DateTime<Utc>,
-    account_no: String,
-    taxamt: f64,
-    trans_type: String,
-    amount: f64,
-    running_balance: f64,
-    running_shares: f64,
-    shares: Option<f64>,
-    price: Option<f64>,
-    netamount: f64,
-    mop: String,
-    currency: String,
-    p_amount: f64,
-    w_amount: f64,
-    i_amount: f64,
-    statement: String,
-}
-
-pub struct Summation {
-    total_running_bal: f64,
-    total_taxs: f64,
-    total_deposits: f64,
-    total_withdrawal: f64,
-    total_interest: f64,
-}
 
+/// The closing figures of a balanced fund statement.
 pub struct BFSummation {
-    total_purchase_units: f64,
-    total_purchase_costs: f64,
-    total_sale_units: f64,
-    total_sale_costs: f64,
-    total_balance_units: f64,
-    latest_nav: f64,
+    total_balance_units: Decimal,
+    latest_nav: Decimal,
     closing_date: DateTime<Utc>,
 }
 
-pub fn create_pdf(data: Vec<Transaction>, pdf_name: String, mmf: bool) {
-    let (w, h) = (210.0, 297.0);
-    let data_len = data.len();
+#[derive(Debug, Clone)]
+pub struct StatementOutput {
+    pub path: PathBuf,
+    pub manifest: Option<PathBuf>,
+    pub pages: usize,
+}
 
-    if data_len < 1 {
-        panic!("Data Error")
-    }
+pub struct RenderedPdf {
+    pub bytes: Vec<u8>,
+    pub pages: usize,
+    pub summary: StatementSummary,
+}
 
-    let first_page_size = 26;
-    let per_page = 31;
+/// Renders the statement and writes it below the output root, where the
+/// payload's `output` configuration points.
+pub fn create_pdf(payload: &Payload, mmf: bool) -> Result<StatementOutput, StatementError> {
+    let rendered = render_pdf(payload, mmf)?;
+    let last = &payload.transactions[payload.transactions.len() - 1];
+    let output = payload.output.clone().unwrap_or_default();
+    let root = output::root();
+
+    let path = output.path_for(
+        &root,
+        &FileNameParts {
+            member_no: &payload.customer.member_no,
+            account_no: &payload.account.account_no,
+            security_code: &payload.fund.security_code,
+            period_end: last.trans_date.format("%Y-%m-%d").to_string(),
+            pdf_name: &payload.pdf_name,
+        },
+    )?;
+    let path = output.write(&root, path, &rendered.bytes)?;
 
-    let total_pages: i64 = if data_len <= first_page_size {
-        1
+    let manifest = if output.manifest {
+        let manifest = StatementManifest::new(&rendered.summary, &path, &rendered.bytes);
+        Some(output.write_manifest(&path, &serde_json::to_vec_pretty(&manifest)?)?)
     } else {
-        let pages = ((data_len - first_page_size) as f64 / per_page as f64).ceil() as i64 + 1;
-        pages
+        None
     };
 
+    Ok(StatementOutput {
+        path,
+        manifest,
+        pages: rendered.pages,
+    })
+}
+
+/// Renders the statement in memory so it can be streamed without touching
+/// the filesystem.
+pub fn render_pdf(payload: &Payload, mmf: bool) -> Result<RenderedPdf, StatementError> {
+    let (w, h) = (210.0, PAGE_HEIGHT);
+    let data = &payload.transactions;
+
+    let warnings = check_payload(payload, mmf)?;
+
+    let branding = payload.branding.clone().unwrap_or_default();
+    let palette = branding.palette()?;
+    let address = branding.address_lines()?;
+    let logo = load_logo(&branding.logo())?;
+    let font_files = font_config(payload).map(FontFiles::load).transpose()?;
+    let metrics = font_files
+        .as_ref()
+        .map_or_else(Metrics::default, FontFiles::metrics);
+
+    let columns = fund_columns(payload, mmf)?;
+    let table = fund_table(&columns, mmf, metrics.clone(), palette, address.len());
+    let pages = layout::paginate(payload, &table)?;
+    let total_pages = pages.len();
+    let footer = Footer::new(&payload.fund.disclaimer, &metrics);
+
     let margin_top = Mm(10.0);
     let margin_bottom = Mm(10.0);
     let margin_left = Mm(10.0);
@@ -90,50 +114,15 @@ pub fn create_pdf(data: Vec<Transaction>, pdf_name: String, mmf: bool) {
 
     let (doc, page, layer) = PdfDocument::new("Full Statement", Mm(w), Mm(h), "layer 1");
 
-    let default_font = doc.add_builtin_font(BuiltinFont::Helvetica).unwrap();
-    let bold_font = doc.add_builtin_font(BuiltinFont::HelveticaBold).unwrap();
-    let user_details = &data[data_len - 1];
-
-    let total_running_bal: f64 = data[data_len - 1].running_balance;
-    let total_taxs: f64 = data.iter().map(|t| t.taxamt).sum();
-    let total_deposits: f64 = data.iter().map(|t| t.p_amount).sum();
-    let total_withdrawal: f64 = data.iter().map(|t| t.w_amount).sum();
-    let total_interest: f64 = data.iter().map(|t| t.i_amount).sum();
-
-    let total_purchase_units: f64 = data
-        .iter()
-        .map(|t| match t.trans_type.as_str() {
-            "PURCHASE" => t.shares.unwrap(),
-            _ => 0.0,
-        })
-        .sum();
-    let total_purchase_costs: f64 = data
-        .iter()
-        .map(|t| match t.trans_type.as_str() {
-            "PURCHASE" => t.amount,
-            _ => 0.0,
-        })
-        .sum();
-    let total_sale_units: f64 = data
-        .iter()
-        .map(|t| match t.trans_type.as_str() {
-            "WITHDRAWAL" => t.shares.unwrap(),
-            _ => 0.0,
-        })
-        .sum();
+    let fonts = Fonts::add(&doc, font_files.as_ref())?;
 
-    let total_sale_costs: f64 = data
-        .iter()
-        .map(|t: &Transaction| match t.trans_type.as_str() {
-            "WITHDRAWAL" => t.amount,
-            _ => 0.0,
-        })
-        .sum();
-    let total_balance_units = data[data_len - 1].running_shares;
-    let latest_nav: f64 = data[data_len - 1].price.unwrap();
-    let closing_date = data[data_len - 1].trans_date;
+    let rounding = payload.rounding.unwrap_or_default();
+    let mut summary = vec![totals_row(&columns, data)];
+    if !mmf {
+        summary.push(closing_row(&columns, &bf_summation(data), &rounding));
+    }
 
-    for p in 0..total_pages {
+    for (p, rows) in pages.iter().cloned().enumerate() {
         let current_layer: PdfLayerReference = if p == 0 {
             doc.get_page(page).get_layer(layer)
         } else {
@@ -145,22 +134,20 @@ pub fn create_pdf(data: Vec<Transaction>, pdf_name: String, mmf: bool) {
         if p == 0 {
             main_header(
                 current_layer.clone(),
-                &default_font,
-                &bold_font,
+                &fonts,
+                &metrics,
+                &branding,
+                &address,
+                &logo,
                 usable_width,
                 usable_height,
                 margin_top,
                 margin_left,
-                &user_details,
-            )
+                payload,
+            )?;
         }
 
-        page_footer(
-            current_layer.clone(),
-            usable_width,
-            &default_font,
-            user_details,
-        );
+        page_footer(current_layer.clone(), &fonts, &footer);
 
         if p > 0 {
             page_header(
@@ -169,229 +156,359 @@ pub fn create_pdf(data: Vec<Transaction>, pdf_name: String, mmf: bool) {
                 usable_width,
                 p + 1,
                 total_pages,
-                &default_font,
-            );
+                fonts.get(Face::Regular),
+                &branding,
+                &logo,
+            )?;
         }
 
-        if mmf {
-            if p == 0 {
-                let first_page_trans: Vec<Transaction> =
-                    data.iter().skip(0).take(first_page_size).cloned().collect();
-                gen_table_mmf(
-                    current_layer,
-                    h - 66.0,
-                    &default_font,
-                    &bold_font,
-                    first_page_trans,
-                    if total_pages == 1 { true } else { false },
-                    Summation {
-                        total_running_bal,
-                        total_taxs,
-                        total_deposits,
-                        total_withdrawal,
-                        total_interest,
-                    },
-                );
-            } else {
-                let trans_data: Vec<Transaction> =
-                    data.iter().skip(first_page_size).cloned().collect();
-                let start_index = per_page * (p - 1) as usize;
-
-                let trans: Vec<Transaction> = trans_data
-                    .iter()
-                    .skip(start_index)
-                    .take(per_page)
-                    .cloned()
-                    .collect();
-                gen_table_mmf(
-                    current_layer,
-                    h - 22.0,
-                    &default_font,
-                    &bold_font,
-                    trans,
-                    if p + 1 == total_pages { true } else { false },
-                    Summation {
-                        total_running_bal,
-                        total_taxs,
-                        total_deposits,
-                        total_withdrawal,
-                        total_interest,
-                    },
-                );
-            }
-        } else {
-            if p == 0 {
-                let first_page_trans: Vec<Transaction> =
-                    data.iter().take(first_page_size).cloned().collect();
-                gen_table(
-                    current_layer,
-                    h - 59.0,
-                    &default_font,
-                    &bold_font,
-                    first_page_trans,
-                    if total_pages == 1 { true } else { false },
-                    BFSummation {
-                        total_purchase_units,
-                        total_purchase_costs,
-                        total_sale_units,
-                        total_sale_costs,
-                        total_balance_units,
-                        latest_nav,
-                        closing_date,
-                    },
-                );
-            } else {
-                let trans_data: Vec<Transaction> =
-                    data.iter().skip(first_page_size).cloned().collect();
-                let start_index = per_page * (p - 1) as usize;
-
-                let trans: Vec<Transaction> = trans_data
-                    .iter()
-                    .skip(start_index)
-                    .take(per_page)
-                    .cloned()
-                    .collect();
-                gen_table(
-                    current_layer,
-                    h - 22.0,
-                    &default_font,
-                    &bold_font,
-                    trans,
-                    if p + 1 == total_pages { true } else { false },
-                    BFSummation {
-                        total_purchase_units,
-                        total_purchase_costs,
-                        total_sale_units,
-                        total_sale_costs,
-                        total_balance_units,
-                        latest_nav,
-                        closing_date,
-                    },
-                );
-            }
+        // a first page left empty under a tall header
+        if rows.is_empty() {
+            continue;
         }
+        let last_page = p + 1 == total_pages;
+        let top = if p == 0 { table.top_first } else { table.top };
+
+        table.draw(
+            &current_layer,
+            top,
+            &fonts,
+            &data[rows],
+            last_page.then_some(summary.as_slice()),
+            &rounding,
+        );
     }
-    let member_no = match &user_details.member_no {
-        m_no => m_no,
-        _ => "n_member_no",
+
+    let bytes = share_images(
+        doc.with_conformance(PdfConformance::X3_2003_PDF_1_4)
+            .save_to_bytes()?,
+    )?;
+
+    Ok(RenderedPdf {
+        bytes,
+        pages: total_pages,
+        summary: summarize(payload, mmf, &columns, &pages, warnings),
+    })
+}
+
+/// Runs the same checks, pagination and summations as [`render_pdf`] and
+/// returns the figures the statement would print, without drawing it.
+pub fn summarize_pdf(payload: &Payload, mmf: bool) -> Result<StatementSummary, StatementError> {
+    let warnings = check_payload(payload, mmf)?;
+    let branding = payload.branding.clone().unwrap_or_default();
+    let palette = branding.palette()?;
+    let address = branding.address_lines()?;
+    let metrics = match font_config(payload) {
+        Some(config) => FontFiles::load(config)?.metrics(),
+        None => Metrics::default(),
     };
+    let columns = fund_columns(payload, mmf)?;
+    let table = fund_table(&columns, mmf, metrics, palette, address.len());
+    let pages = layout::paginate(payload, &table)?;
 
-    let mut writer = BufWriter::new(
-        File::create(format!("storage/{}/{}-temp.pdf", member_no, pdf_name)).unwrap(),
-    );
+    Ok(summarize(payload, mmf, &columns, &pages, warnings))
+}
+
+fn summarize(
+    payload: &Payload,
+    mmf: bool,
+    columns: &[ColumnConfig],
+    pages: &[Range<usize>],
+    warnings: Vec<ValidationIssue>,
+) -> StatementSummary {
+    let data = &payload.transactions;
+    let last = &data[data.len() - 1];
+    let summation = columns
+        .iter()
+        .zip(totals_row(columns, data))
+        .map(|(config, total)| ColumnTotal {
+            field: config.field,
+            total: match total {
+                Cell::Number(total) => Some(total.to_string()),
+                _ => None,
+            },
+        })
+        .collect();
+
+    let (market_value, mmf_totals, bf_totals) = if mmf {
+        let totals = DefaultTotals::new(mmf_columns(), data).mmf();
+        (last.running_balance, Some(totals), None)
+    } else {
+        let bf_summation = bf_summation(data);
+        let totals = DefaultTotals::new(bf_columns(), data).bf();
+        (
+            bf_summation.latest_nav * bf_summation.total_balance_units,
+            None,
+            Some(totals),
+        )
+    };
+
+    StatementSummary {
+        member_no: payload.customer.member_no.clone(),
+        account_no: payload.account.account_no.clone(),
+        security_code: payload.fund.security_code.clone(),
+        pages: pages.len(),
+        rows_per_page: pages.iter().map(|rows| rows.len()).collect(),
+        period_start: data[0].trans_date.format("%Y-%m-%d").to_string(),
+        closing_date: last.trans_date.format("%Y-%m-%d").to_string(),
+        market_value: market_value.to_string(),
+        summation,
+        mmf_totals,
+        bf_totals,
+        warnings,
+    }
+}
+
+/// The payload's own columns, or the fund's default layout.
+/// The fund's table layout when the payload lists no `columns`.
+pub fn default_columns(mmf: bool) -> Vec<ColumnConfig> {
+    if mmf {
+        mmf_columns()
+    } else {
+        bf_columns()
+    }
+}
+
+fn fund_columns(payload: &Payload, mmf: bool) -> Result<Vec<ColumnConfig>, StatementError> {
+    let Some(columns) = &payload.columns else {
+        return Ok(default_columns(mmf));
+    };
+    check_columns(columns)?;
+
+    Ok(columns.clone())
+}
+
+fn fund_table(
+    columns: &[ColumnConfig],
+    mmf: bool,
+    metrics: Metrics,
+    palette: Palette,
+    address_lines: usize,
+) -> Table {
+    let columns = columns.iter().map(|c| c.column(&palette)).collect();
+    let table = Table::new(columns);
+    let table = Table {
+        top_first: table.top_first - header_overflow(address_lines),
+        metrics,
+        palette,
+        ..table
+    };
+    if mmf {
+        return table;
+    }
+
+    // balanced fund rows are denser and close with the market value
+    Table {
+        line_height: 2.7,
+        font_size: 7.0,
+        summary_rows: 2,
+        ..table
+    }
+}
+
+/// Rejects payloads that cannot be rendered and returns the remaining
+/// warnings.
+fn check_payload(payload: &Payload, mmf: bool) -> Result<Vec<ValidationIssue>, StatementError> {
+    if payload.transactions.is_empty() {
+        return Err(StatementError::InvalidPayload(
+            "no transactions to render".to_string(),
+        ));
+    }
+
+    let issues = validate(payload, mmf);
+    if issues.iter().any(|issue| issue.is_error()) {
+        return Err(StatementError::Validation(issues));
+    }
+
+    Ok(issues)
+}
+
+/// The summation row of a fund's default columns, read by field.
+struct DefaultTotals {
+    columns: Vec<ColumnConfig>,
+    totals: Vec<Cell>,
+}
+
+impl DefaultTotals {
+    fn new(columns: Vec<ColumnConfig>, data: &[Transaction]) -> Self {
+        let totals = totals_row(&columns, data);
+        DefaultTotals { columns, totals }
+    }
+
+    fn get(&self, field: Field) -> String {
+        let total = self
+            .columns
+            .iter()
+            .position(|c| c.field == field)
+            .map(|i| &self.totals[i]);
+        match total {
+            Some(Cell::Number(total)) => total.to_string(),
+            _ => Decimal::ZERO.to_string(),
+        }
+    }
+
+    fn mmf(&self) -> MmfTotals {
+        MmfTotals {
+            running_balance: self.get(Field::RunningBalance),
+            tax: self.get(Field::Taxamt),
+            deposits: self.get(Field::Deposit),
+            withdrawals: self.get(Field::Withdrawal),
+            interest: self.get(Field::Interest),
+        }
+    }
+
+    fn bf(&self) -> BfTotals {
+        BfTotals {
+            purchase_units: self.get(Field::PurchaseUnits),
+            purchase_costs: self.get(Field::PurchaseCost),
+            sale_units: self.get(Field::SaleUnits),
+            sale_costs: self.get(Field::SaleCost),
+            balance_units: self.get(Field::RunningShares),
+            latest_nav: self.get(Field::Price),
+        }
+    }
+}
 
-    doc.with_conformance(PdfConformance::X3_2003_PDF_1_4)
-        .save(&mut writer)
-        .unwrap();
+fn bf_summation(data: &[Transaction]) -> BFSummation {
+    let last = &data[data.len() - 1];
+
+    BFSummation {
+        total_balance_units: last.running_shares,
+        latest_nav: last.price.unwrap_or_default(),
+        closing_date: last.trans_date,
+    }
+}
+
+/// Room for the customer's details, up to just short of the company address,
+/// and for the address block itself, up to the right margin; in mm.
+const CUSTOMER_WIDTH: f32 = 135.0;
+const ADDRESS_WIDTH: f32 = 50.0;
+/// The address block is set at a 12pt line height. The header was laid out
+/// for eight lines, the member and account numbers below them clearing the
+/// fund title.
+const ADDRESS_LINE_HEIGHT: f32 = 12.0;
+const DEFAULT_ADDRESS_LINES: usize = 8;
+
+/// How far a longer address block pushes the fund title and the first
+/// page's table down, in mm.
+fn header_overflow(address_lines: usize) -> f32 {
+    address_lines.saturating_sub(DEFAULT_ADDRESS_LINES) as f32 * ADDRESS_LINE_HEIGHT * MM_PER_PT
 }
 
+#[allow(clippy::too_many_arguments)]
 fn main_header(
     current_layer: PdfLayerReference,
-    default_font: &IndirectFontRef,
-    bold_font: &IndirectFontRef,
+    fonts: &Fonts,
+    metrics: &Metrics,
+    branding: &Branding,
+    address: &[String],
+    logo: &Logo,
     usable_width: Mm,
     usable_height: Mm,
     margin_top: Mm,
     margin_left: Mm,
-    user_details: &Transaction,
-) {
-    let logo = load_logo();
-    logo.add_to_layer(
+    payload: &Payload,
+) -> Result<(), StatementError> {
+    let customer = &payload.customer;
+    let closing_date = payload.transactions[payload.transactions.len() - 1].trans_date;
+
+    let scale = 0.7 * branding.logo_scale()?;
+    logo.image().add_to_layer(
         current_layer.clone(),
         ImageTransform {
             translate_x: Some(Mm(0.0) + Mm(7.0)),
             translate_y: Some(usable_height - margin_top - Mm(5.0)),
-            scale_x: Some(0.7),
-            scale_y: Some(0.7),
+            scale_x: Some(scale),
+            scale_y: Some(scale),
             ..Default::default()
         },
     );
 
+    // each line shrinks, then truncates, rather than run into its neighbour
+    let font = fonts.get(Face::Regular);
+    let write_line = |text: &str, max_width: f32| {
+        let (text, size) = metrics.fit_line(text, Face::Regular, 8.5, max_width);
+        current_layer.set_font(font, size);
+        current_layer.write_text(text, font);
+        current_layer.add_line_break();
+    };
+
     //customer details
     current_layer.begin_text_section();
     current_layer.set_text_cursor(Mm(0.0) + margin_left, usable_height - Mm(23.0));
-    current_layer.set_font(&default_font, 8.5);
     current_layer.set_line_height(12.0);
-    current_layer.write_text(format!("{}", user_details.allnames), &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text(
-        format!("P.O Box: {}", user_details.post_address),
-        &default_font,
-    );
-    current_layer.add_line_break();
-    current_layer.write_text(format!("Email: {}", user_details.e_mail), &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text(format!("Tel. No. {}", user_details.gsm_no), &default_font);
+    for line in [
+        customer.name.clone(),
+        format!("P.O Box: {}", customer.post_address),
+        format!("Email: {}", customer.email),
+        format!("Tel. No. {}", customer.phone),
+    ] {
+        write_line(&line, CUSTOMER_WIDTH);
+    }
     current_layer.end_text_section();
 
     //address
     current_layer.begin_text_section();
-    current_layer.set_font(&default_font, 8.5);
+    current_layer.set_font(font, 8.5);
     current_layer.set_text_cursor(
         usable_width - Mm(40.0),
         usable_height + margin_top - Mm(5.0),
     );
-    current_layer.set_line_height(12.0);
-    current_layer.write_text("P.O Box: 59485-00200", &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text("Nairobi, Kenya", &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text("Tel: 2823000", &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text("Fax: 2823344", &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text("CIC Plaza Mara Road,", &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text("Upper Hill.", &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text("cic.asset@cic.co.ke", &default_font);
-    current_layer.add_line_break();
-    current_layer.write_text("www.cic.co.ke", &default_font);
+    current_layer.set_line_height(ADDRESS_LINE_HEIGHT);
+    for line in address {
+        write_line(line, ADDRESS_WIDTH);
+    }
 
     current_layer.add_line_break();
-    current_layer.add_line_break();
-    current_layer.write_text(
-        format!("Member No. {}", user_details.member_no),
-        &default_font,
-    );
-    current_layer.add_line_break();
-    current_layer.write_text(
-        format!("Account No. {}", user_details.account_no),
-        &default_font,
+    write_line(&format!("Member No. {}", customer.member_no), ADDRESS_WIDTH);
+    write_line(
+        &format!("Account No. {}", payload.account.account_no),
+        ADDRESS_WIDTH,
     );
 
     current_layer.end_text_section();
 
-    current_layer.use_text(
-        format!(
+    let (title, size) = metrics.fit_line(
+        &format!(
             "{} | {} | {}",
-            user_details.descript,
-            user_details.trans_date.format("%d-%m-%Y"),
-            user_details.currency
+            payload.fund.name,
+            closing_date.format("%d-%m-%Y"),
+            payload.fund.currency
         ),
+        Face::Bold,
         9.0,
+        ADDRESS_WIDTH,
+    );
+    current_layer.use_text(
+        title,
+        size,
         usable_width - Mm(40.0),
-        usable_height - Mm(45.0),
-        bold_font,
+        usable_height - Mm(45.0 + header_overflow(address.len())),
+        fonts.get(Face::Bold),
     );
+
+    Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn page_header(
     layer: PdfLayerReference,
     usable_height: Mm,
     usable_width: Mm,
-    page: i64,
-    total_pages: i64,
+    page: usize,
+    total_pages: usize,
     font: &IndirectFontRef,
-) {
-    let logo = load_logo();
-    logo.add_to_layer(
+    branding: &Branding,
+    logo: &Logo,
+) -> Result<(), StatementError> {
+    let scale = 0.4 * branding.logo_scale()?;
+    logo.image().add_to_layer(
         layer.clone(),
         ImageTransform {
             translate_x: Some(Mm(0.0) + Mm(7.0)),
             translate_y: Some(usable_height + Mm(2.0)),
-            scale_x: Some(0.4),
-            scale_y: Some(0.4),
+            scale_x: Some(scale),
+            scale_y: Some(scale),
             ..Default::default()
         },
     );
@@ -403,71 +520,81 @@ fn page_header(
         usable_height + Mm(7.0),
         font,
     );
-}
 
-fn page_footer(
-    layer: PdfLayerReference,
-    usable_width: Mm,
-    font: &IndirectFontRef,
-    fund_details: &Transaction,
-) {
-    let paragraph = &fund_details.statement;
-
-    let wrapped_text = wrap(paragraph, 100);
-    for (i, line) in wrapped_text.iter().enumerate() {
-        let text_width = line.len();
-        let center_x = (usable_width - Mm(text_width as f32)) / 2.0;
-        let y = Mm(9.0) - Mm(i as f32 * 3.0);
-        layer.use_text(line.to_string(), 6.5, center_x, y, font)
-    }
+    Ok(())
 }
 
-fn load_logo() -> Image {
-    let logo_file = load_image("assets/Logo.jpg");
-
-    let image_file = ImageXObject {
-        width: Px(logo_file.width() as usize),
-        height: Px(logo_file.height() as usize),
-        color_space: printpdf::ColorSpace::Rgb,
-        bits_per_component: printpdf::ColorBits::Bit8,
-        interpolate: true,
-        image_data: logo_file.into_bytes(),
-        image_filter: None,
-        clipping_bbox: None,
-    };
-
-    return Image::from(image_file);
+fn page_footer(layer: PdfLayerReference, fonts: &Fonts, footer: &Footer) {
+    for line in &footer.lines {
+        let mut x = line.x();
+        for span in &line.spans {
+            let font = fonts.get(span.face);
+            layer.use_text(&span.text, FOOTER_SIZE, Mm(x), Mm(line.y), font);
+            x += span.width;
+        }
+    }
 }
 
-fn load_image(image_path: &str) -> DynamicImage {
-    let img = open(&Path::new(image_path)).unwrap();
-    img
+/// The payload's own fonts, or else those of its branding.
+fn font_config(payload: &Payload) -> Option<&FontConfig> {
+    payload
+        .fonts
+        .as_ref()
+        .or_else(|| payload.branding.as_ref()?.fonts.as_ref())
 }
 
-pub fn round_decimal(num: f64) -> String {
-    if has_leading_zeros_after_decimal(num) && num < 1.0 {
-        let rounded_number = (num * 10000.0 as f64).round() / 10000.0;
-        let final_num = format!("{:.4}", rounded_number);
-        return final_num;
-    }
-    // let rounded_number = (num * 100.0 as f64).round() / 100.0;
-    let mut f: Formatter;
-    f = "[.2n/,]".parse().unwrap();
-    let formatted_number = format!("{}", f.fmt2(num));
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
 
-    return formatted_number;
-}
+    use super::summarize_pdf;
+    use crate::pdf::{
+        columns::{ColumnConfig, Field},
+        fixtures::{payload, row},
+        payload::TransactionType,
+    };
 
-fn has_leading_zeros_after_decimal(number: f64) -> bool {
-    let num_str = number.to_string();
+    #[test]
+    fn dry_run_totals_match_the_printed_columns() {
+        let mut purchase = row(1, TransactionType::Purchase, dec!(1000), dec!(1000));
+        purchase.p_amount = dec!(990);
+        let mut interest = row(2, TransactionType::Interest, dec!(10), dec!(1009.5));
+        interest.i_amount = dec!(9.5);
+        interest.taxamt = dec!(-0.5);
+        let mut payload = payload(vec![purchase, interest]);
+        payload.columns = Some(
+            [
+                Field::TransDate,
+                Field::PAmount,
+                Field::IAmount,
+                Field::Taxamt,
+            ]
+            .into_iter()
+            .map(ColumnConfig::new)
+            .collect(),
+        );
 
-    if let Some(decimal_position) = num_str.find('.') {
-        let decimal_part = &num_str[decimal_position + 1..];
+        let summary = summarize_pdf(&payload, true).unwrap();
+        let summation: Vec<_> = summary
+            .summation
+            .iter()
+            .map(|c| (c.field, c.total.as_deref()))
+            .collect();
+        assert_eq!(
+            summation,
+            [
+                (Field::TransDate, None),
+                (Field::PAmount, Some("990")),
+                (Field::IAmount, Some("9.5")),
+                (Field::Taxamt, Some("0.5")),
+            ]
+        );
 
-        if decimal_part.chars().take_while(|&c| c == '0').count() >= 2 {
-            return true;
-        }
+        // the fund's own totals still follow the default columns
+        let totals = summary.mmf_totals.unwrap();
+        assert_eq!(totals.deposits, "1000");
+        assert_eq!(totals.interest, "10");
+        assert_eq!(totals.tax, "0.5");
+        assert_eq!(totals.running_balance, "1009.5");
     }
-
-    false
 }
@@ -1,365 +1,134 @@
-use printpdf::{Color, IndirectFontRef, Line, Mm, PdfLayerReference, Point, Rgb};
-
-use super::{round_decimal, BFSummation, Transaction};
-
-pub fn gen_table(
-    layer: PdfLayerReference,
-    top_pos: f32,
-    font: &IndirectFontRef,
-    bold_font: &IndirectFontRef,
-    transactions: Vec<Transaction>,
-    summations: bool,
-    sums: BFSummation,
-) {
-    let cell_padding = 5.0;
-    let table_x = 10.0;
-    let table_y = top_pos;
-    let row_height = 8.0;
-    let header_column_widths = vec![47.0, 47.0, 47.0, 47.0];
-    let column_widths = vec![
-        10.0, 12.0, 15.0, 13.0, 10.0, 13.0, 15.0, 12.0, 15.0, 15.0, 10.0,
-    ];
-    let columns = vec![
-        "Trans Id",
-        "Trans Date",
-        "Description",
-        "Units",
-        "Price",
-        "Cost",
-        "Units",
-        "Price",
-        "Cost",
-        "Units",
-        "Nav",
-    ];
-
-    let red = Rgb::new(190.0 / 256.0, 0.0 / 256.0, 0.0 / 256.0, None);
-    let _gold = Rgb::new(255.0 / 256.0, 215.0 / 256.0, 0.0 / 256.0, None);
-    let _dark = Rgb::new(80.0 / 256.0, 80.0 / 256.0, 80.0 / 256.0, None);
-    let gray = Rgb::new(230.0 / 256.0, 230.0 / 256.0, 230.0 / 256.0, None);
-
-    let colors = table_colors();
-
-    for (row_index, _row_data) in (0..1).enumerate() {
-        for (col_index, header) in ["", "Purchases", "Sales", "Balance"].iter().enumerate() {
-            let text_len = header.len();
-            let col_width = header_column_widths[..col_index].iter().sum::<f32>();
-            let hx = table_x
-                + (col_width + (text_len * col_index) as f32 / 2.0)
-                + cell_padding * col_index as f32;
-            let hy = table_y - row_height - row_index as f32 * row_height - cell_padding;
-
-            let points = vec![
-                (
-                    Point::new(Mm(hx), Mm(hy - row_height + cell_padding)),
-                    false,
-                ),
-                (
-                    Point::new(Mm(table_x + 190.0), Mm(hy - row_height + cell_padding)),
-                    false,
-                ),
-            ];
-            let line = Line {
-                points,
-                is_closed: false,
-            };
-
-            let color = if col_index == 1 {
-                Rgb::new(244.0 / 256.0, 164.0 / 256.0, 96.0 / 256.0, None)
-            } else if col_index == 2 {
-                Rgb::new(87.0 / 256.0, 75.0 / 256.0, 144.0 / 256.0, None)
-            } else {
-                Rgb::new(80.0 / 256.0, 80.0 / 256.0, 80.0 / 256.0, None)
-            };
-
-            layer.set_outline_thickness(0.8);
-            layer.set_outline_color(Color::Rgb(red.clone()));
-            layer.add_line(line);
-            layer.set_fill_color(printpdf::Color::Rgb(color.clone()));
-            layer.use_text(header.to_string(), 8.0, Mm(hx), Mm(hy), bold_font);
-        }
-
-        for (col_index, (title, color)) in columns.iter().zip(colors.iter()).enumerate() {
-            let col_width = column_widths[..col_index].iter().sum::<f32>();
-            let hx = table_x + col_width + cell_padding * col_index as f32;
-            let hy =
-                table_y - (row_height as f32 * 2.0) - row_index as f32 * row_height - cell_padding;
-            layer.set_fill_color(Color::Rgb(color.clone()));
-            layer.use_text(title.to_string(), 7.5, Mm(hx), Mm(hy), bold_font);
+use super::{
+    columns::{ColumnConfig, Field},
+    BFSummation, Cell, Rounding,
+};
+
+/// Balanced fund statements: units and cost of every purchase and sale, with
+/// the running units and the NAV they were priced at.
+pub fn bf_columns() -> Vec<ColumnConfig> {
+    vec![
+        ColumnConfig::new(Field::TransId)
+            .title("Trans Id")
+            .width(14.0),
+        ColumnConfig::new(Field::TransDate).width(17.0),
+        ColumnConfig::new(Field::Mop).width(24.0).size(6.0),
+        ColumnConfig::new(Field::PurchaseUnits).width(17.0),
+        ColumnConfig::new(Field::PurchasePrice).width(14.0),
+        ColumnConfig::new(Field::PurchaseCost).width(19.0),
+        ColumnConfig::new(Field::SaleUnits).width(17.0),
+        ColumnConfig::new(Field::SalePrice).width(14.0),
+        ColumnConfig::new(Field::SaleCost).width(19.0),
+        ColumnConfig::new(Field::RunningShares)
+            .title("Units")
+            .width(19.0)
+            .group("Balance"),
+        ColumnConfig::new(Field::Price)
+            .title("Nav")
+            .width(16.0)
+            .group("Balance"),
+    ]
+}
 
-            let points = vec![
-                (
-                    Point::new(Mm(hx), Mm(hy - row_height + cell_padding)),
-                    false,
-                ),
-                (
-                    Point::new(Mm(table_x + 190.0), Mm(hy - row_height + cell_padding)),
-                    false,
-                ),
-            ];
-            let line = Line {
-                points,
-                is_closed: false,
-            };
-            layer.set_outline_thickness(0.7);
-            layer.set_outline_color(Color::Rgb(gray.clone()));
-            layer.add_line(line);
-        }
+/// The market value under the unit balance, labelled in the column before
+/// it, and the closing date in the first column left over. The value goes
+/// under the last column instead when the balance isn't shown or has no
+/// column before it for the label.
+pub fn closing_row(columns: &[ColumnConfig], sums: &BFSummation, rounding: &Rounding) -> Vec<Cell> {
+    let mut row = vec![Cell::Empty; columns.len()];
+    let value_col = columns
+        .iter()
+        .position(|c| c.field == Field::RunningShares)
+        .filter(|&i| i > 0)
+        .unwrap_or(columns.len() - 1);
+    let label_col = value_col.checked_sub(1);
+    let market_value = sums.latest_nav * sums.total_balance_units;
+
+    // priced like an amount, not units
+    row[value_col] = Cell::Text(rounding.amount.format(market_value));
+    if let Some(label_col) = label_col {
+        row[label_col] = Cell::Text("Market Value:".to_string());
     }
-
-    for (row_index, trans) in transactions.iter().enumerate() {
-        for (col_index, (_cell_data, color)) in (0..=10).zip(colors.iter()).enumerate() {
-            let start_y = table_y - (row_height as f32 * 3.0);
-            let col_width = column_widths[..col_index].iter().sum::<f32>();
-            let x = table_x + col_width + cell_padding * col_index as f32;
-            let y = start_y - row_index as f32 * row_height - cell_padding;
-
-            let points = vec![
-                (Point::new(Mm(x), Mm(y - row_height + cell_padding)), false),
-                (
-                    Point::new(Mm(table_x + 190.0), Mm(y - row_height + cell_padding)),
-                    false,
-                ),
-            ];
-            let line = Line {
-                points,
-                is_closed: false,
-            };
-            layer.set_outline_thickness(0.7);
-            layer.set_outline_color(Color::Rgb(gray.clone()));
-            layer.set_fill_color(Color::Rgb(color.clone()));
-
-            match col_index {
-                0 => layer.use_text(trans.trans_id.to_string(), 7.0, Mm(x), Mm(y), font),
-                1 => layer.use_text(
-                    trans.trans_date.format("%Y-%m-%d").to_string(),
-                    7.0,
-                    Mm(x),
-                    Mm(y),
-                    font,
-                ),
-                2 => layer.use_text(trans.mop.to_string(), 6.0, Mm(x), Mm(y), font),
-                3 => {
-                    if trans.trans_type == "PURCHASE" {
-                        layer.use_text(
-                            format!("{}", round_decimal(trans.shares.unwrap())),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        )
-                    }
-                }
-                4 => {
-                    if trans.trans_type == "PURCHASE" {
-                        layer.use_text(
-                            format!("{}", round_decimal(trans.price.unwrap())),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        )
-                    }
-                }
-                5 => {
-                    if trans.trans_type == "PURCHASE" {
-                        layer.use_text(
-                            format!("{}", round_decimal(trans.amount)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        )
-                    }
-                }
-                6 => {
-                    if trans.trans_type == "WITHDRAWAL" {
-                        layer.use_text(
-                            format!("{}", round_decimal(trans.shares.unwrap())),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        )
-                    }
-                }
-                7 => {
-                    if trans.trans_type == "WITHDRAWAL" {
-                        layer.use_text(
-                            format!("{}", round_decimal(trans.price.unwrap())),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        )
-                    }
-                }
-                8 => {
-                    if trans.trans_type == "WITHDRAWAL" {
-                        layer.use_text(
-                            format!("{}", round_decimal(trans.amount)), //cost
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        )
-                    }
-                }
-                9 => layer.use_text(
-                    format!("{}", round_decimal(trans.running_shares)),
-                    7.0,
-                    Mm(x),
-                    Mm(y),
-                    font,
-                ),
-                10 => layer.use_text(
-                    format!("{}", round_decimal(trans.price.unwrap())),
-                    7.0,
-                    Mm(x),
-                    Mm(y),
-                    font,
-                ),
-                _ => println!(""),
-            }
-
-            layer.add_line(line);
-        }
+    // tables too narrow for a free column print no date
+    if let Some(date_col) = (0..columns.len()).find(|&i| i != value_col && Some(i) != label_col) {
+        row[date_col] = Cell::Text(format!(
+            "Closing balance as at: {}",
+            sums.closing_date.format("%Y-%m-%d")
+        ));
     }
 
-    if summations {
-        let last_row_index = transactions.len() as f32 + 3.0;
-        for (row_index, _row_data) in (0..=1).enumerate() {
-            for (col_index, (_cell_data, _color)) in (0..=10).zip(colors.iter()).enumerate() {
-                let col_width = column_widths[..col_index].iter().sum::<f32>();
-                let x = table_x + col_width + cell_padding * col_index as f32;
-                // let y = table_y - last_row_index * row_height - cell_padding;
-                let start_y = table_y - last_row_index * row_height;
-                let y = start_y - row_index as f32 * row_height - cell_padding;
-
-                let points = vec![
-                    (Point::new(Mm(x), Mm(y - row_height + cell_padding)), false),
-                    (
-                        Point::new(Mm(table_x + 190.0), Mm(y - row_height + cell_padding)),
-                        false,
-                    ),
-                ];
-                let line = Line {
-                    points,
-                    is_closed: false,
-                };
-                layer.set_outline_thickness(0.7);
-                layer.set_outline_color(Color::Rgb(red.clone()));
-                layer.set_fill_color(Color::Rgb(red.clone()));
+    row
+}
 
-                if row_index == 0 {
-                    match col_index {
-                        0 => layer.use_text("Summations".to_string(), 7.0, Mm(x), Mm(y), font),
-                        1 => (),
-                        2 => (),
-                        3 => layer.use_text(
-                            format!("{}", round_decimal(sums.total_purchase_units)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        ),
-                        4 => (),
-                        5 => layer.use_text(
-                            format!("{}", round_decimal(sums.total_purchase_costs)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        ),
-                        6 => layer.use_text(
-                            format!("{}", round_decimal(sums.total_sale_units)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        ),
-                        7 => (),
-                        8 => layer.use_text(
-                            format!("{}", round_decimal(sums.total_sale_costs)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        ),
-                        9 => layer.use_text(
-                            format!("{}", round_decimal(sums.total_balance_units)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        ),
-                        10 => layer.use_text(
-                            format!("{}", round_decimal(sums.latest_nav)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        ),
-                        _ => (),
-                    }
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::{bf_columns, closing_row};
+    use crate::pdf::{
+        columns::{ColumnConfig, Field},
+        fixtures::unit_row,
+        payload::TransactionType,
+        rounding::Rounding,
+        table::Cell,
+        util::bf_summation,
+    };
+
+    const DATE: &str = "Closing balance as at: 2023-01-02";
+
+    fn closing(fields: &[Field]) -> Vec<Cell> {
+        let columns: Vec<_> = fields.iter().map(|&f| ColumnConfig::new(f)).collect();
+        closing_with(&columns)
+    }
 
-                    layer.add_line(line.clone());
-                }
+    fn closing_with(columns: &[ColumnConfig]) -> Vec<Cell> {
+        use TransactionType::*;
 
-                if row_index > 0 {
-                    let msg = format!(
-                        "Closing balance as at: {}",
-                        sums.closing_date.format("%Y-%m-%d").to_string()
-                    );
+        let rows = [
+            unit_row(1, Purchase, dec!(1000), dec!(1000), dec!(100), dec!(100)),
+            unit_row(2, Withdrawal, dec!(-200), dec!(800), dec!(-20), dec!(80)),
+        ];
+        closing_row(columns, &bf_summation(&rows), &Rounding::default())
+    }
 
-                    let bal = sums.latest_nav * sums.total_balance_units;
+    fn text(text: &str) -> Cell {
+        Cell::Text(text.to_string())
+    }
 
-                    match col_index {
-                        0 => layer.use_text(msg, 7.0, Mm(x), Mm(y), font),
-                        1 => (),
-                        2 => (),
-                        3 => (),
-                        4 => (),
-                        5 => (),
-                        6 => (),
-                        7 => (),
-                        8 => layer.use_text("Market Value:", 7.0, Mm(x), Mm(y), font),
-                        9 => layer.use_text(
-                            format!("{}", round_decimal(bal)),
-                            7.0,
-                            Mm(x),
-                            Mm(y),
-                            font,
-                        ),
-                        10 => (),
-                        _ => (),
-                    }
+    #[test]
+    fn market_value_under_the_unit_balance() {
+        let row = closing_with(&bf_columns());
 
-                    layer.add_line(line);
-                }
-            }
-        }
+        assert_eq!(row[0], text(DATE));
+        assert_eq!(row[8], text("Market Value:"));
+        assert_eq!(row[9], text("800.00"));
+        assert_eq!(row[10], Cell::Empty);
     }
-}
 
-fn table_colors() -> Vec<Rgb> {
-    let _red = Rgb::new(190.0 / 256.0, 0.0 / 256.0, 0.0 / 256.0, None);
-    let brown = Rgb::new(244.0 / 256.0, 164.0 / 256.0, 96.0 / 256.0, None);
-    let _gray = Rgb::new(230.0 / 256.0, 230.0 / 256.0, 230.0 / 256.0, None);
-    let dark = Rgb::new(80.0 / 256.0, 80.0 / 256.0, 80.0 / 256.0, None);
-    let blue = Rgb::new(87.0 / 256.0, 75.0 / 256.0, 144.0 / 256.0, None);
+    #[test]
+    fn market_value_moves_to_the_last_column() {
+        // without a unit balance
+        assert_eq!(
+            closing(&[Field::TransDate, Field::Mop, Field::Price]),
+            [text(DATE), text("Market Value:"), text("800.00")]
+        );
+        // with the unit balance first, leaving no room for its label
+        assert_eq!(
+            closing(&[Field::RunningShares, Field::Mop, Field::Price]),
+            [text(DATE), text("Market Value:"), text("800.00")]
+        );
+    }
 
-    vec![
-        dark.clone(),
-        dark.clone(),
-        dark.clone(),
-        brown.clone(),
-        brown.clone(),
-        brown.clone(),
-        blue.clone(),
-        blue.clone(),
-        blue.clone(),
-        dark.clone(),
-        dark.clone(),
-    ]
+    #[test]
+    fn date_takes_the_first_free_column() {
+        assert_eq!(
+            closing(&[Field::Mop, Field::RunningShares, Field::Price]),
+            [text("Market Value:"), text("800.00"), text(DATE)]
+        );
+        // too narrow for the date
+        assert_eq!(
+            closing(&[Field::Mop, Field::RunningShares]),
+            [text("Market Value:"), text("800.00")]
+        );
+        assert_eq!(closing(&[Field::RunningShares]), [text("800.00")]);
+    }
 }
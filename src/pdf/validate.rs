@@ -0,0 +1,475 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tslink::tslink;
+
+use super::{
+    columns::{printed_step, Field},
+    payload::{Movement, Payload, Transaction},
+    util::default_columns,
+};
+
+/// One problem found in a payload's rows. Errors stop the statement from
+/// rendering; warnings are reported but do not.
+#[tslink]
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationIssue {
+    pub code: String,
+    pub severity: Severity,
+    pub row: Option<usize>,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+#[tslink]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl ValidationIssue {
+    fn error(code: &str, row: usize, field: &str, message: String) -> Self {
+        ValidationIssue {
+            code: code.to_string(),
+            severity: Severity::Error,
+            row: Some(row),
+            field: Some(field.to_string()),
+            message,
+        }
+    }
+
+    fn warning(code: &str, row: usize, field: &str, message: String) -> Self {
+        ValidationIssue {
+            severity: Severity::Warning,
+            ..ValidationIssue::error(code, row, field, message)
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Checks every row and returns all problems in row order instead of
+/// stopping at the first one, so source data can be fixed in one pass.
+/// Balanced funds (`mmf == false`) also need units and prices to add up.
+///
+/// Running balances and units may be off by one step of the places their
+/// columns print: ledgers often round them that way while amounts carry
+/// more, which leaves each row up to a step away from the exact sum. Columns
+/// printing the exact value allow no difference.
+///
+/// Balanced fund statements are priced from their units, so a running balance
+/// that doesn't add up is only a warning there.
+pub fn validate(payload: &Payload, mmf: bool) -> Vec<ValidationIssue> {
+    let rows = &payload.transactions;
+    let default_columns = default_columns(mmf);
+    let columns = payload.columns.as_deref().unwrap_or(&default_columns);
+    let balance_tolerance = printed_step(columns, Field::RunningBalance);
+    let units_tolerance = printed_step(columns, Field::RunningShares);
+    let mut issues = vec![];
+    let mut trans_ids: HashMap<i64, usize> = HashMap::new();
+
+    for (row, t) in rows.iter().enumerate() {
+        if !mmf {
+            check_unit_fields(row, t, &mut issues);
+        }
+        check_identity(payload, row, t, &mut issues);
+
+        match trans_ids.entry(t.trans_id) {
+            Entry::Occupied(first) => issues.push(ValidationIssue::error(
+                "DUPLICATE_TRANS_ID",
+                row,
+                "trans_id",
+                format!(
+                    "trans_id {} on row {} was already used on row {}",
+                    t.trans_id,
+                    row,
+                    first.get()
+                ),
+            )),
+            Entry::Vacant(entry) => {
+                entry.insert(row);
+            }
+        }
+
+        let Some(prev) = row.checked_sub(1).map(|i| &rows[i]) else {
+            continue;
+        };
+
+        if t.trans_date < prev.trans_date {
+            issues.push(ValidationIssue::warning(
+                "UNSORTED_DATES",
+                row,
+                "trans_date",
+                format!(
+                    "trans_date {} on row {} is before row {}",
+                    t.trans_date.format("%Y-%m-%d"),
+                    row,
+                    row - 1
+                ),
+            ));
+        }
+
+        let expected = prev.running_balance + balance_change(t);
+        if (expected - t.running_balance).abs() > balance_tolerance {
+            let issue = if mmf {
+                ValidationIssue::error
+            } else {
+                ValidationIssue::warning
+            };
+            issues.push(issue(
+                "BALANCE_MISMATCH",
+                row,
+                "running_balance",
+                format!(
+                    "running_balance {} on row {} does not follow from row {}: expected {}",
+                    t.running_balance,
+                    row,
+                    row - 1,
+                    expected
+                ),
+            ));
+        }
+
+        if !mmf {
+            if let Some(change) = units_change(t) {
+                let expected = prev.running_shares + change;
+                if (expected - t.running_shares).abs() > units_tolerance {
+                    issues.push(ValidationIssue::error(
+                        "SHARES_MISMATCH",
+                        row,
+                        "running_shares",
+                        format!(
+                            "running_shares {} on row {} does not follow from row {}: expected {}",
+                            t.running_shares,
+                            row,
+                            row - 1,
+                            expected
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Balanced fund rows need a price for the NAV column, and anything that
+/// moves units needs its units.
+fn check_unit_fields(row: usize, t: &Transaction, issues: &mut Vec<ValidationIssue>) {
+    let missing = |field: &str| {
+        ValidationIssue::error(
+            "MISSING_FIELD",
+            row,
+            field,
+            format!("missing field `{}` on row {}", field, row),
+        )
+    };
+
+    if t.price.is_none() {
+        issues.push(missing("price"));
+    }
+    if t.movement() != Movement::Income && t.shares.is_none() {
+        issues.push(missing("shares"));
+    }
+}
+
+/// Rows that name a member or account must agree with the payload, which
+/// catches legacy payloads mixing several members' rows.
+fn check_identity(
+    payload: &Payload,
+    row: usize,
+    t: &Transaction,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let checks = [
+        (
+            "MEMBER_MISMATCH",
+            "member_no",
+            &t.member_no,
+            &payload.customer.member_no,
+        ),
+        (
+            "ACCOUNT_MISMATCH",
+            "account_no",
+            &t.account_no,
+            &payload.account.account_no,
+        ),
+    ];
+
+    for (code, field, value, expected) in checks {
+        match value {
+            Some(value) if value != expected => issues.push(ValidationIssue::error(
+                code,
+                row,
+                field,
+                format!(
+                    "{} {:?} on row {} does not match the statement's {:?}",
+                    field, value, row, expected
+                ),
+            )),
+            _ => (),
+        }
+    }
+}
+
+fn balance_change(t: &Transaction) -> Decimal {
+    let amount = t.amount.abs();
+    let change = match t.movement() {
+        Movement::Deposit | Movement::Income => amount,
+        Movement::Withdrawal => -amount,
+    };
+
+    change - t.taxamt.abs()
+}
+
+/// `None` when the row should move units but doesn't say how many; that is
+/// already reported as a missing field.
+fn units_change(t: &Transaction) -> Option<Decimal> {
+    match t.movement() {
+        Movement::Income => Some(Decimal::ZERO),
+        Movement::Deposit => t.shares.map(|s| s.abs()),
+        Movement::Withdrawal => t.shares.map(|s| -s.abs()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::{validate, Severity, ValidationIssue};
+    use crate::pdf::{
+        columns::{ColumnConfig, Field},
+        fixtures::{payload, row, unit_row},
+        payload::{Transaction, TransactionType},
+        table::NumberFormat,
+    };
+
+    fn codes(issues: &[ValidationIssue]) -> Vec<(&str, Option<usize>)> {
+        issues
+            .iter()
+            .map(|issue| (issue.code.as_str(), issue.row))
+            .collect()
+    }
+
+    #[test]
+    fn balance_follows_every_movement() {
+        use TransactionType::*;
+
+        let mut taxed = row(3, Interest, dec!(50), dec!(1045));
+        taxed.taxamt = dec!(-5);
+        let rows = vec![
+            row(1, Purchase, dec!(1000), dec!(1000)),
+            row(2, SwitchIn, dec!(0), dec!(1000)),
+            taxed,
+            row(4, Withdrawal, dec!(-100), dec!(945)),
+            row(5, Dividend, dec!(5), dec!(950)),
+            row(6, Reinvestment, dec!(5), dec!(955)),
+            row(7, Fee, dec!(5), dec!(950)),
+            row(8, TransferIn, dec!(100), dec!(1050)),
+            row(9, TransferOut, dec!(50), dec!(1000)),
+            row(10, SwitchOut, dec!(100), dec!(900)),
+        ];
+
+        assert!(validate(&payload(rows), true).is_empty());
+    }
+
+    #[test]
+    fn reversals_and_adjustments_follow_their_sign() {
+        use TransactionType::*;
+
+        let rows = vec![
+            row(1, Purchase, dec!(1000), dec!(1000)),
+            row(2, Reversal, dec!(-1000), dec!(0)),
+            row(3, Adjustment, dec!(20), dec!(20)),
+            row(4, Adjustment, dec!(-5), dec!(15)),
+        ];
+        assert!(validate(&payload(rows), true).is_empty());
+
+        // a negative reversal taken as a deposit would need 2000
+        let rows = vec![
+            row(1, Purchase, dec!(1000), dec!(1000)),
+            row(2, Reversal, dec!(-1000), dec!(2000)),
+        ];
+        assert_eq!(
+            codes(&validate(&payload(rows), true)),
+            [("BALANCE_MISMATCH", Some(1))]
+        );
+    }
+
+    #[test]
+    fn balance_mismatch_is_an_error() {
+        let rows = vec![
+            row(1, TransactionType::Purchase, dec!(1000), dec!(1000)),
+            row(2, TransactionType::Purchase, dec!(500), dec!(1600)),
+            row(3, TransactionType::Purchase, dec!(100), dec!(1700)),
+        ];
+        let issues = validate(&payload(rows), true);
+
+        assert_eq!(codes(&issues), [("BALANCE_MISMATCH", Some(1))]);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].field.as_deref(), Some("running_balance"));
+    }
+
+    #[test]
+    fn balance_rounded_to_printed_places_passes() {
+        // amounts accrue at four places while the ledger rounds balances to two
+        let rows = vec![
+            row(1, TransactionType::Purchase, dec!(1000), dec!(1000)),
+            row(2, TransactionType::Interest, dec!(0.1234), dec!(1000.12)),
+            row(3, TransactionType::Interest, dec!(0.1234), dec!(1000.25)),
+            row(4, TransactionType::Interest, dec!(0.1234), dec!(1000.37)),
+        ];
+        assert!(validate(&payload(rows), true).is_empty());
+
+        let rows = vec![
+            row(1, TransactionType::Purchase, dec!(1000), dec!(1000)),
+            row(2, TransactionType::Interest, dec!(0.1234), dec!(1000.14)),
+        ];
+        assert_eq!(
+            codes(&validate(&payload(rows), true)),
+            [("BALANCE_MISMATCH", Some(1))]
+        );
+    }
+
+    #[test]
+    fn figures_printed_exactly_must_add_up_exactly() {
+        use TransactionType::*;
+
+        let rows = vec![
+            unit_row(1, Purchase, dec!(1000), dec!(1000), dec!(100), dec!(100)),
+            unit_row(2, Interest, dec!(0.1234), dec!(1000.12), dec!(0), dec!(100)),
+            unit_row(
+                3,
+                Purchase,
+                dec!(10),
+                dec!(1010.12),
+                dec!(1.0049),
+                dec!(101),
+            ),
+        ];
+        let mut payload = payload(rows);
+        assert!(validate(&payload, true).is_empty());
+        assert!(validate(&payload, false).is_empty());
+
+        let exact = |field| ColumnConfig {
+            format: Some(NumberFormat::Text),
+            ..ColumnConfig::new(field)
+        };
+        payload.columns = Some(vec![
+            exact(Field::RunningBalance),
+            exact(Field::RunningShares),
+        ]);
+        assert_eq!(
+            codes(&validate(&payload, true)),
+            [("BALANCE_MISMATCH", Some(1))]
+        );
+        assert_eq!(
+            codes(&validate(&payload, false)),
+            [("BALANCE_MISMATCH", Some(1)), ("SHARES_MISMATCH", Some(2))]
+        );
+    }
+
+    #[test]
+    fn balanced_fund_balance_mismatch_is_a_warning() {
+        use TransactionType::*;
+
+        let rows = vec![
+            unit_row(1, Purchase, dec!(1000), dec!(1000), dec!(100), dec!(100)),
+            unit_row(2, Purchase, dec!(500), dec!(1600), dec!(50), dec!(150)),
+        ];
+        let issues = validate(&payload(rows), false);
+
+        assert_eq!(codes(&issues), [("BALANCE_MISMATCH", Some(1))]);
+        assert_eq!(issues[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn shares_follow_units_for_balanced_funds() {
+        use TransactionType::*;
+
+        let rows = vec![
+            unit_row(1, Purchase, dec!(1000), dec!(1000), dec!(100), dec!(100)),
+            unit_row(2, Interest, dec!(10), dec!(1010), dec!(0), dec!(100)),
+            unit_row(3, Withdrawal, dec!(-200), dec!(810), dec!(-20), dec!(80)),
+            unit_row(4, Reversal, dec!(-100), dec!(710), dec!(10), dec!(70)),
+        ];
+        assert!(validate(&payload(rows), false).is_empty());
+
+        let rows = vec![
+            unit_row(1, Purchase, dec!(1000), dec!(1000), dec!(100), dec!(100)),
+            unit_row(2, Withdrawal, dec!(-200), dec!(800), dec!(20), dec!(120)),
+        ];
+        let payload = payload(rows);
+        assert_eq!(
+            codes(&validate(&payload, false)),
+            [("SHARES_MISMATCH", Some(1))]
+        );
+        // money market funds don't track units
+        assert!(validate(&payload, true).is_empty());
+    }
+
+    #[test]
+    fn balanced_funds_need_units_and_prices() {
+        let mut purchase = row(1, TransactionType::Purchase, dec!(1000), dec!(1000));
+        purchase.running_shares = dec!(100);
+        let income = Transaction {
+            price: Some(dec!(10)),
+            running_shares: dec!(100),
+            ..row(2, TransactionType::Interest, dec!(10), dec!(1010))
+        };
+
+        let issues = validate(&payload(vec![purchase, income]), false);
+        let fields: Vec<_> = issues.iter().map(|i| i.field.as_deref()).collect();
+
+        assert_eq!(fields, [Some("price"), Some("shares")]);
+        assert!(issues.iter().all(|i| i.code == "MISSING_FIELD"));
+    }
+
+    #[test]
+    fn duplicate_trans_id_is_an_error() {
+        let mut second = row(2, TransactionType::Purchase, dec!(1), dec!(2));
+        second.trans_id = 1;
+        let rows = vec![row(1, TransactionType::Purchase, dec!(1), dec!(1)), second];
+        let issues = validate(&payload(rows), true);
+
+        assert_eq!(codes(&issues), [("DUPLICATE_TRANS_ID", Some(1))]);
+        assert!(issues[0].is_error());
+    }
+
+    #[test]
+    fn unsorted_dates_are_a_warning() {
+        let mut rows = vec![
+            row(1, TransactionType::Purchase, dec!(1), dec!(1)),
+            row(2, TransactionType::Purchase, dec!(1), dec!(2)),
+        ];
+        rows[1].trans_date = rows[0].trans_date - chrono::Duration::days(1);
+        let issues = validate(&payload(rows), true);
+
+        assert_eq!(codes(&issues), [("UNSORTED_DATES", Some(1))]);
+        assert!(!issues[0].is_error());
+    }
+
+    #[test]
+    fn rows_must_match_member_and_account() {
+        let mut rows = vec![
+            row(1, TransactionType::Purchase, dec!(1), dec!(1)),
+            row(2, TransactionType::Purchase, dec!(1), dec!(2)),
+            row(3, TransactionType::Purchase, dec!(1), dec!(3)),
+        ];
+        rows[0].member_no = Some("00020".to_string());
+        rows[0].account_no = Some("001-00020-001".to_string());
+        rows[1].member_no = Some("00021".to_string());
+        rows[2].account_no = Some("001-00021-001".to_string());
+
+        assert_eq!(
+            codes(&validate(&payload(rows), true)),
+            [("MEMBER_MISMATCH", Some(1)), ("ACCOUNT_MISMATCH", Some(2))]
+        );
+    }
+}